@@ -0,0 +1,159 @@
+use std::{
+    fmt,
+    ops::{Add, Mul},
+};
+
+// Little-endian base 2^32 limbs with no trailing zero limbs, so zero is the
+// empty vector and derived equality is value equality.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn normalized(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0, |acc, &limb| (acc << 32) | limb as u128),
+        )
+    }
+
+    fn div_rem_small(&self, divisor: u32) -> (Self, u32) {
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut rem: u64 = 0;
+
+        for (i, &limb) in self.limbs.iter().enumerate().rev() {
+            let acc = (rem << 32) | limb as u64;
+            quotient[i] = (acc / divisor as u64) as u32;
+            rem = acc % divisor as u64;
+        }
+
+        (Self::normalized(quotient), rem as u32)
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut n: u128) -> Self {
+        let mut limbs = Vec::new();
+        while n > 0 {
+            limbs.push(n as u32);
+            n >>= 32;
+        }
+        Self { limbs }
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry: u64 = 0;
+
+        for i in 0..len {
+            let a = self.limbs.get(i).copied().unwrap_or(0) as u64;
+            let b = other.limbs.get(i).copied().unwrap_or(0) as u64;
+            let sum = a + b + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+
+        BigUint::normalized(limbs)
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry: u64 = 0;
+
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let acc = limbs[i + j] as u64 + a as u64 * b as u64 + carry;
+                limbs[i + j] = acc as u32;
+                carry = acc >> 32;
+            }
+
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+
+        BigUint::normalized(limbs)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut chunks = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (quotient, rem) = n.div_rem_small(1_000_000_000);
+            chunks.push(rem);
+            n = quotient;
+        }
+
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u128_round_trip() {
+        for n in [0, 1, u32::MAX as u128 + 1, u128::MAX] {
+            assert_eq!(BigUint::from(n).to_u128(), Some(n));
+        }
+
+        let big = &BigUint::from(u128::MAX) + &BigUint::from(1);
+        assert_eq!(big.to_u128(), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let max = BigUint::from(u128::MAX);
+        assert_eq!(
+            (&max + &max).to_string(),
+            "680564733841876926926749214863536422910"
+        );
+        assert_eq!(
+            (&max * &max).to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        assert_eq!((&max * &BigUint::default()).to_string(), "0");
+        assert_eq!(
+            (&BigUint::from(1_000_000_007) * &BigUint::from(3)).to_string(),
+            "3000000021"
+        );
+    }
+}
@@ -1,8 +1,12 @@
-use aoc2025_rs::read_lines;
+use std::num::NonZeroU16;
 
-const MAX_NEIGHBOURS: usize = 3;
+use aoc2025_rs::{
+    arg_value, has_flag,
+    image::{Gif, Rgb, luma, pgm, ppm},
+    read_lines, timed,
+};
 
-const ADJ: &[(i32, i32)] = &[
+const MOORE: &[(i32, i32)] = &[
     (-1, -1),
     (0, -1),
     (1, -1),
@@ -13,68 +17,574 @@ const ADJ: &[(i32, i32)] = &[
     (1, 1),
 ];
 
-fn find_accessible(grid: &[Vec<char>]) -> Vec<(usize, usize)> {
+const VON_NEUMANN: &[(i32, i32)] = &[(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const HEX_EVEN_ROW: &[(i32, i32)] = &[(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+
+const HEX_ODD_ROW: &[(i32, i32)] = &[(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Neighbourhood {
+    Moore(i32),
+    VonNeumann,
+    Hex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules {
+    neighbourhood: Neighbourhood,
+    max_neighbours: usize,
+    wrap: bool,
+    roll: char,
+    empty: char,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            neighbourhood: Neighbourhood::Moore(1),
+            max_neighbours: 3,
+            wrap: false,
+            roll: '@',
+            empty: '.',
+        }
+    }
+}
+
+impl Rules {
+    fn offsets(&self, odd_row: bool) -> Vec<(i32, i32)> {
+        match self.neighbourhood {
+            Neighbourhood::Moore(1) => MOORE.to_vec(),
+            Neighbourhood::Moore(r) => (-r..=r)
+                .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+                .filter(|&offset| offset != (0, 0))
+                .collect(),
+            Neighbourhood::VonNeumann => VON_NEUMANN.to_vec(),
+            Neighbourhood::Hex if odd_row => HEX_ODD_ROW.to_vec(),
+            Neighbourhood::Hex => HEX_EVEN_ROW.to_vec(),
+        }
+    }
+
+    fn neighbours(&self, width: usize, height: usize) -> Neighbours {
+        let (w, h) = (width as i32, height as i32);
+
+        // Offsets that agree modulo the grid size hit the same cell from every
+        // position, so wrapped tables are reduced once here instead of per cell.
+        let table = |odd_row: bool| {
+            let offsets = self.offsets(odd_row);
+            if !self.wrap {
+                return offsets;
+            }
+
+            let mut wrapped = Vec::new();
+            for (dx, dy) in offsets {
+                let offset = (dx.rem_euclid(w), dy.rem_euclid(h));
+                if offset != (0, 0) && !wrapped.contains(&offset) {
+                    wrapped.push(offset);
+                }
+            }
+            wrapped
+        };
+
+        Neighbours {
+            width: w,
+            height: h,
+            wrap: self.wrap,
+            tables: [table(false), table(true)],
+        }
+    }
+}
+
+// Offset tables for even and odd rows of one grid size.
+struct Neighbours {
+    width: i32,
+    height: i32,
+    wrap: bool,
+    tables: [Vec<(i32, i32)>; 2],
+}
+
+impl Neighbours {
+    fn of(&self, x: usize, y: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = (x as i32, y as i32);
+
+        self.tables[(y % 2) as usize]
+            .iter()
+            .filter_map(move |&(dx, dy)| {
+                let (mut nx, mut ny) = (x + dx, y + dy);
+
+                if self.wrap {
+                    nx %= self.width;
+                    ny %= self.height;
+                } else if nx < 0 || nx >= self.width || ny < 0 || ny >= self.height {
+                    return None;
+                }
+
+                Some((ny * self.width + nx) as usize)
+            })
+    }
+}
+
+fn find_accessible(grid: &[Vec<char>], rules: &Rules) -> Vec<(usize, usize)> {
     let mut coords = Vec::new();
-    let height = grid.len() as i32;
-    let width = grid[0].len() as i32;
+    let height = grid.len();
+    let width = grid[0].len();
+    let neighbours = rules.neighbours(width, height);
 
     for (y, row) in grid.iter().enumerate() {
         for (x, cell) in row.iter().enumerate() {
-            if *cell == '.' {
+            if *cell != rules.roll {
                 continue;
             }
 
-            let mut adj_count = 0;
+            let adj_count = neighbours
+                .of(x, y)
+                .filter(|&n| grid[n / width][n % width] == rules.roll)
+                .count();
 
-            for (dx, dy) in ADJ {
-                let nx = x as i32 + dx;
-                let ny = y as i32 + dy;
+            if adj_count <= rules.max_neighbours {
+                coords.push((x, y));
+            }
+        }
+    }
 
-                if nx < 0 || nx >= width {
-                    continue;
-                }
+    coords
+}
 
-                if ny < 0 || ny >= height {
-                    continue;
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitGrid {
+    width: usize,
+    height: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    fn new(grid: &[Vec<char>], roll: char) -> Self {
+        let height = grid.len();
+        let width = grid[0].len();
+        let stride = width.div_ceil(64);
+        let mut words = vec![0; stride * height];
+
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if cell == roll {
+                    words[y * stride + x / 64] |= 1 << (x % 64);
                 }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            stride,
+            words,
+        }
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    fn accessible(&self, max_neighbours: usize) -> Vec<u64> {
+        let empty = vec![0; self.stride];
+        let mut mask = vec![0; self.words.len()];
+
+        for y in 0..self.height {
+            let above = if y > 0 { self.row(y - 1) } else { &empty };
+            let row = self.row(y);
+            let below = if y + 1 < self.height {
+                self.row(y + 1)
+            } else {
+                &empty
+            };
+
+            for i in 0..self.stride {
+                let planes = [
+                    shift_west(above, i),
+                    above[i],
+                    shift_east(above, i),
+                    shift_west(row, i),
+                    shift_east(row, i),
+                    shift_west(below, i),
+                    below[i],
+                    shift_east(below, i),
+                ];
 
-                if grid[ny as usize][nx as usize] == '@' {
-                    adj_count += 1;
+                let mut sum = [0u64; 4];
+                for plane in planes {
+                    let mut carry = plane;
+                    for bit in sum.iter_mut() {
+                        let next = *bit & carry;
+                        *bit ^= carry;
+                        carry = next;
+                    }
                 }
+
+                mask[y * self.stride + i] = row[i] & !exceeds(&sum, max_neighbours);
             }
+        }
 
-            if adj_count <= MAX_NEIGHBOURS {
-                coords.push((x, y));
+        mask
+    }
+
+    fn coords(&self, mask: &[u64]) -> Vec<(usize, usize)> {
+        let mut coords = Vec::new();
+
+        for (i, &word) in mask.iter().enumerate() {
+            let mut bits = word;
+            while bits != 0 {
+                let bit = bits.trailing_zeros() as usize;
+                coords.push(((i % self.stride) * 64 + bit, i / self.stride));
+                bits &= bits - 1;
             }
         }
+
+        coords
     }
+}
 
-    coords
+fn shift_west(row: &[u64], i: usize) -> u64 {
+    let carry = if i > 0 { row[i - 1] >> 63 } else { 0 };
+    (row[i] << 1) | carry
 }
 
-fn part2(grid: &[Vec<char>]) -> usize {
-    let mut grid = grid.to_owned();
-    let mut count = 0;
+fn shift_east(row: &[u64], i: usize) -> u64 {
+    let carry = if i + 1 < row.len() {
+        row[i + 1] << 63
+    } else {
+        0
+    };
+    (row[i] >> 1) | carry
+}
+
+fn exceeds(sum: &[u64; 4], threshold: usize) -> u64 {
+    if threshold >= 15 {
+        return 0;
+    }
 
-    loop {
-        let coords = find_accessible(&grid);
+    let mut greater = 0;
+    let mut equal = !0;
 
-        if coords.is_empty() {
-            break;
+    for bit in (0..4).rev() {
+        if (threshold >> bit) & 1 == 0 {
+            greater |= equal & sum[bit];
+            equal &= !sum[bit];
+        } else {
+            equal &= sum[bit];
         }
+    }
+
+    greater
+}
+
+// The bit-packed counter only knows the non-wrapping Moore(1) neighbourhood,
+// so it takes just the parts of `Rules` it can honour.
+fn find_accessible_packed(
+    grid: &[Vec<char>],
+    roll: char,
+    max_neighbours: usize,
+) -> Vec<(usize, usize)> {
+    let bits = BitGrid::new(grid, roll);
+    bits.coords(&bits.accessible(max_neighbours))
+}
+
+#[derive(Debug)]
+struct Erosion {
+    width: usize,
+    height: usize,
+    rolls: Vec<bool>,
+    rounds: Vec<usize>,
+    removed_in: Vec<Option<usize>>,
+}
+
+fn erode(grid: &[Vec<char>], rules: &Rules) -> Erosion {
+    let height = grid.len();
+    let width = grid[0].len();
+
+    let mut present: Vec<bool> = grid
+        .iter()
+        .flatten()
+        .map(|&cell| cell == rules.roll)
+        .collect();
+    let mut counts: Vec<usize> = vec![0; width * height];
+    let mut queued: Vec<bool> = vec![false; width * height];
+    let mut frontier: Vec<usize> = Vec::new();
+    let neighbours = rules.neighbours(width, height);
 
-        count += coords.len();
+    for i in (0..width * height).filter(|&i| present[i]) {
+        counts[i] = neighbours
+            .of(i % width, i / width)
+            .filter(|&n| present[n])
+            .count();
 
-        for (x, y) in coords {
-            grid[y][x] = '.';
+        if counts[i] <= rules.max_neighbours {
+            queued[i] = true;
+            frontier.push(i);
         }
     }
 
-    count
+    let rolls = present.clone();
+    let mut rounds = Vec::new();
+    let mut removed_in = vec![None; width * height];
+
+    while !frontier.is_empty() {
+        rounds.push(frontier.len());
+
+        for &i in &frontier {
+            present[i] = false;
+            removed_in[i] = Some(rounds.len());
+        }
+
+        let mut next = Vec::new();
+
+        for &i in &frontier {
+            for n in neighbours.of(i % width, i / width) {
+                if !present[n] {
+                    continue;
+                }
+
+                counts[n] -= 1;
+
+                if counts[n] <= rules.max_neighbours && !queued[n] {
+                    queued[n] = true;
+                    next.push(n);
+                }
+            }
+        }
+
+        frontier = next;
+    }
+
+    Erosion {
+        width,
+        height,
+        rolls,
+        rounds,
+        removed_in,
+    }
 }
 
-fn part1(grid: &[Vec<char>]) -> usize {
-    find_accessible(grid).len()
+const ROUND_SYMBOLS: &[u8] = b"123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+impl Erosion {
+    fn stable(&self) -> Vec<bool> {
+        (0..self.width * self.height)
+            .map(|i| self.rolls[i] && self.removed_in[i].is_none())
+            .collect()
+    }
+
+    fn render(&self, cell: impl Fn(usize) -> String, separator: &str) -> String {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| cell(y * self.width + x))
+                    .collect::<Vec<_>>()
+                    .join(separator)
+                    + "\n"
+            })
+            .collect()
+    }
+
+    fn stable_text(&self, rules: &Rules) -> String {
+        let stable = self.stable();
+        self.render(
+            |i| if stable[i] { rules.roll } else { rules.empty }.to_string(),
+            "",
+        )
+    }
+
+    // Rounds past the last symbol are shown as '+'; the CSV has exact values.
+    fn heatmap_text(&self, rules: &Rules) -> String {
+        self.render(
+            |i| match self.removed_in[i] {
+                Some(r) => ROUND_SYMBOLS
+                    .get(r - 1)
+                    .map_or('+', |&c| c as char)
+                    .to_string(),
+                None if self.rolls[i] => rules.roll.to_string(),
+                None => rules.empty.to_string(),
+            },
+            "",
+        )
+    }
+
+    fn stable_csv(&self) -> String {
+        let stable = self.stable();
+        self.render(|i| (stable[i] as u8).to_string(), ",")
+    }
+
+    // 0 marks a roll that is never removed, a blank field an empty cell.
+    fn heatmap_csv(&self) -> String {
+        self.render(
+            |i| match self.removed_in[i] {
+                Some(r) => r.to_string(),
+                None if self.rolls[i] => "0".to_string(),
+                None => String::new(),
+            },
+            ",",
+        )
+    }
+}
+
+const EMPTY_COLOUR: Rgb = [0, 0, 0];
+const ROLL_COLOUR: Rgb = [230, 230, 230];
+const ROUND_COLOURS: usize = 254;
+
+fn round_colour(i: usize) -> Rgb {
+    let hue = 240.0 * i as f64 / (ROUND_COLOURS - 1) as f64;
+    let x = 1.0 - ((hue / 60.0) % 2.0 - 1.0).abs();
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        _ => (0.0, x, 1.0),
+    };
+
+    [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8]
+}
+
+impl Erosion {
+    fn palette() -> Vec<Rgb> {
+        let mut palette = vec![EMPTY_COLOUR, ROLL_COLOUR];
+        palette.extend((0..ROUND_COLOURS).map(round_colour));
+        palette
+    }
+
+    fn frame(&self, round: usize) -> Vec<u8> {
+        let last = self.rounds.len().max(2) - 1;
+
+        (0..self.width * self.height)
+            .map(|i| match self.removed_in[i] {
+                Some(r) if r <= round => 2 + ((r - 1) * (ROUND_COLOURS - 1) / last) as u8,
+                _ if self.rolls[i] => 1,
+                _ => 0,
+            })
+            .collect()
+    }
+
+    fn frames(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        (0..=self.rounds.len()).map(|round| self.frame(round))
+    }
+}
+
+fn upscale(frame: &[u8], width: usize, scale: usize) -> Vec<u8> {
+    frame
+        .chunks(width)
+        .flat_map(|row| {
+            let row: Vec<u8> = row
+                .iter()
+                .flat_map(|&cell| std::iter::repeat_n(cell, scale))
+                .collect();
+            std::iter::repeat_n(row, scale).flatten()
+        })
+        .collect()
+}
+
+fn animate(erosion: &Erosion, mode: &str, out: &str, delay_ms: u64, scale: usize) {
+    let palette = Erosion::palette();
+    let (width, height) = (erosion.width * scale, erosion.height * scale);
+
+    match mode {
+        "ansi" => {
+            for frame in erosion.frames() {
+                let mut screen = String::from("\x1b[2J\x1b[H");
+                for row in frame.chunks(erosion.width) {
+                    for &cell in row {
+                        let [r, g, b] = palette[cell as usize];
+                        screen.push_str(&format!("\x1b[48;2;{r};{g};{b}m  "));
+                    }
+                    screen.push_str("\x1b[0m\n");
+                }
+                print!("{}", screen);
+                std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+            }
+        }
+        "ppm" | "pgm" => {
+            std::fs::create_dir_all(out).expect("Failed to create frame directory");
+
+            for (round, frame) in erosion.frames().enumerate() {
+                let pixels = upscale(&frame, erosion.width, scale);
+                let colours: Vec<Rgb> = pixels.iter().map(|&p| palette[p as usize]).collect();
+                let bytes = if mode == "ppm" {
+                    ppm(width, height, &colours)
+                } else {
+                    pgm(
+                        width,
+                        height,
+                        &colours.into_iter().map(luma).collect::<Vec<_>>(),
+                    )
+                };
+
+                std::fs::write(format!("{}/frame_{:03}.{}", out, round, mode), bytes)
+                    .expect("Failed to write frame");
+            }
+        }
+        "gif" => {
+            let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height))
+            else {
+                panic!(
+                    "Scaled grid is {}x{}, but GIF dimensions are limited to {}",
+                    width,
+                    height,
+                    u16::MAX
+                );
+            };
+            let delay_cs = u16::try_from(delay_ms / 10).unwrap_or(u16::MAX);
+
+            let mut gif = Gif::new(gif_width, gif_height, &palette);
+            for frame in erosion.frames() {
+                gif.add_frame(&upscale(&frame, erosion.width, scale), delay_cs);
+            }
+            std::fs::write(out, gif.finish()).expect("Failed to write GIF");
+        }
+        _ => panic!("Unknown animation mode {}", mode),
+    }
+}
+
+fn part2(erosion: &Erosion) -> usize {
+    erosion.rounds.iter().sum()
+}
+
+fn part1(grid: &[Vec<char>], rules: &Rules) -> usize {
+    if rules.neighbourhood == Neighbourhood::Moore(1) && !rules.wrap {
+        find_accessible_packed(grid, rules.roll, rules.max_neighbours).len()
+    } else {
+        find_accessible(grid, rules).len()
+    }
+}
+
+fn random_grid(size: usize, seed: u64) -> Vec<Vec<char>> {
+    let mut state = seed;
+
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    if (state >> 33) % 100 < 60 { '@' } else { '.' }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn bench() {
+    let rules = &Rules::default();
+
+    for size in [100, 1_000, 10_000] {
+        let grid = random_grid(size, size as u64);
+        let naive = timed!(
+            format!("{size}x{size} find_accessible"),
+            find_accessible(&grid, rules).len()
+        );
+        let packed = timed!(
+            format!("{size}x{size} find_accessible_packed"),
+            find_accessible_packed(&grid, rules.roll, rules.max_neighbours).len()
+        );
+        assert_eq!(naive, packed);
+    }
 }
 
 fn lines_to_grid<S: AsRef<str>>(lines: &[S]) -> Vec<Vec<char>> {
@@ -84,10 +594,73 @@ fn lines_to_grid<S: AsRef<str>>(lines: &[S]) -> Vec<Vec<char>> {
         .collect()
 }
 
+fn rules_from_args() -> Rules {
+    let defaults = Rules::default();
+    let radius = arg_value("--radius").map_or(1, |r| {
+        let radius: NonZeroU16 = r.parse().expect("Radius must be a positive integer");
+        i32::from(radius.get())
+    });
+    let symbol = |flag, default| {
+        arg_value(flag).map_or(default, |s| s.chars().next().expect("Empty symbol"))
+    };
+
+    Rules {
+        neighbourhood: match arg_value("--neighbourhood").as_deref() {
+            None | Some("moore") => Neighbourhood::Moore(radius),
+            Some("von-neumann") => Neighbourhood::VonNeumann,
+            Some("hex") => Neighbourhood::Hex,
+            Some(other) => panic!("Unknown neighbourhood {}", other),
+        },
+        max_neighbours: arg_value("--threshold").map_or(defaults.max_neighbours, |t| {
+            t.parse().expect("Invalid threshold")
+        }),
+        wrap: has_flag("--wrap"),
+        roll: symbol("--roll", defaults.roll),
+        empty: symbol("--empty", defaults.empty),
+    }
+}
+
 fn main() {
+    if has_flag("--bench") {
+        bench();
+        return;
+    }
+
+    let rules = rules_from_args();
+
     let input = lines_to_grid(&read_lines(4));
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    println!("Part 1: {}", part1(&input, &rules));
+
+    let erosion = erode(&input, &rules);
+    println!("Part 2: {}", part2(&erosion));
+    println!("Rounds: {:?}", erosion.rounds);
+
+    if let Some(mode) = arg_value("--animate") {
+        let out = arg_value("--out").unwrap_or_else(|| match mode.as_str() {
+            "gif" => "day04.gif".to_string(),
+            _ => "frames".to_string(),
+        });
+        let delay = arg_value("--delay").map_or(100, |d| d.parse().expect("Invalid delay"));
+        let scale = arg_value("--scale").map_or(1, |s| s.parse().expect("Invalid scale"));
+
+        animate(&erosion, &mode, &out, delay, scale);
+    }
+
+    if has_flag("--stable") {
+        print!("{}", erosion.stable_text(&rules));
+    }
+
+    if has_flag("--heatmap") {
+        print!("{}", erosion.heatmap_text(&rules));
+    }
+
+    if let Some(dir) = arg_value("--csv") {
+        std::fs::create_dir_all(&dir).expect("Failed to create CSV directory");
+        std::fs::write(format!("{}/stable.csv", dir), erosion.stable_csv())
+            .expect("Failed to write stable.csv");
+        std::fs::write(format!("{}/rounds.csv", dir), erosion.heatmap_csv())
+            .expect("Failed to write rounds.csv");
+    }
 }
 
 #[cfg(test)]
@@ -108,12 +681,182 @@ mod tests {
     #[test]
     fn test_part1() {
         let input = lines_to_grid(&FIXTURE.lines().collect::<Vec<_>>());
-        assert_eq!(part1(&input), 13);
+        assert_eq!(part1(&input, &Rules::default()), 13);
     }
 
     #[test]
     fn test_part2() {
         let input = lines_to_grid(&FIXTURE.lines().collect::<Vec<_>>());
-        assert_eq!(part2(&input), 43);
+        assert_eq!(part2(&erode(&input, &Rules::default())), 43);
+    }
+
+    #[test]
+    fn test_erode_rounds() {
+        let input = lines_to_grid(&FIXTURE.lines().collect::<Vec<_>>());
+        assert_eq!(
+            erode(&input, &Rules::default()).rounds,
+            vec![13, 12, 7, 5, 2, 1, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn test_stable_grid() {
+        let input = lines_to_grid(&FIXTURE.lines().collect::<Vec<_>>());
+        let erosion = erode(&input, &Rules::default());
+        assert_eq!(
+            erosion.stable_text(&Rules::default()),
+            "..........
+..........
+..........
+....@@....
+...@@@@...
+...@@@@@..
+...@.@.@@.
+...@@.@@@.
+...@@@@@..
+....@@@...
+"
+        );
+        assert!(erosion.stable_csv().starts_with("0,0,0,0,0,0,0,0,0,0\n"));
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let input = lines_to_grid(&["@@@.", "@@@."]);
+        let erosion = erode(&input, &Rules::default());
+        assert_eq!(erosion.heatmap_text(&Rules::default()), "121.\n121.\n");
+        assert_eq!(erosion.heatmap_csv(), "1,2,1,\n1,2,1,\n");
+
+        let input = lines_to_grid(&FIXTURE.lines().collect::<Vec<_>>());
+        let heatmap = erode(&input, &Rules::default()).heatmap_text(&Rules::default());
+        assert_eq!(heatmap.lines().next(), Some("..11.1121."));
+        assert_eq!(heatmap.matches('@').count(), 28);
+    }
+
+    #[test]
+    fn test_wrapped_neighbour_tables() {
+        let rules = Rules {
+            wrap: true,
+            ..Rules::default()
+        };
+        let mut cells: Vec<usize> = rules.neighbours(2, 2).of(0, 0).collect();
+        cells.sort_unstable();
+        assert_eq!(cells, vec![1, 2, 3]);
+
+        let rules = Rules {
+            neighbourhood: Neighbourhood::Moore(2),
+            wrap: true,
+            ..Rules::default()
+        };
+        assert_eq!(rules.neighbours(3, 1).of(1, 0).count(), 2);
+    }
+
+    #[test]
+    fn test_neighbourhoods() {
+        let input = lines_to_grid(&FIXTURE.lines().collect::<Vec<_>>());
+        let cases = [
+            (Neighbourhood::VonNeumann, 1, false, 11, 16),
+            (Neighbourhood::Hex, 2, false, 14, 36),
+            (Neighbourhood::Moore(2), 10, false, 20, 71),
+            (Neighbourhood::Moore(1), 3, true, 2, 3),
+        ];
+
+        for (neighbourhood, max_neighbours, wrap, accessible, removed) in cases {
+            let rules = Rules {
+                neighbourhood,
+                max_neighbours,
+                wrap,
+                ..Rules::default()
+            };
+            assert_eq!(part1(&input, &rules), accessible, "{:?}", rules);
+            assert_eq!(part2(&erode(&input, &rules)), removed, "{:?}", rules);
+        }
+    }
+
+    #[test]
+    fn test_find_accessible_packed() {
+        let input = lines_to_grid(&FIXTURE.lines().collect::<Vec<_>>());
+
+        for max_neighbours in 0..=8 {
+            let rules = Rules {
+                max_neighbours,
+                ..Rules::default()
+            };
+            let mut expected = find_accessible(&input, &rules);
+            expected.sort_by_key(|&(x, y)| (y, x));
+            assert_eq!(
+                find_accessible_packed(&input, rules.roll, max_neighbours),
+                expected
+            );
+        }
+
+        for size in [63, 64, 65, 130] {
+            let grid = random_grid(size, 7);
+            let rules = Rules::default();
+            let mut expected = find_accessible(&grid, &rules);
+            expected.sort_by_key(|&(x, y)| (y, x));
+            assert_eq!(
+                find_accessible_packed(&grid, rules.roll, rules.max_neighbours),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_frames() {
+        let input = lines_to_grid(&["@@@.", "@@@."]);
+        let erosion = erode(&input, &Rules::default());
+        assert_eq!(erosion.rounds, vec![4, 2]);
+        assert_eq!(
+            erosion.removed_in,
+            vec![
+                Some(1),
+                Some(2),
+                Some(1),
+                None,
+                Some(1),
+                Some(2),
+                Some(1),
+                None
+            ]
+        );
+
+        let frames: Vec<Vec<u8>> = erosion.frames().collect();
+        assert_eq!(
+            frames,
+            vec![
+                vec![1, 1, 1, 0, 1, 1, 1, 0],
+                vec![2, 1, 2, 0, 2, 1, 2, 0],
+                vec![2, 255, 2, 0, 2, 255, 2, 0],
+            ]
+        );
+        assert_eq!(upscale(&[1, 2], 2, 2), vec![1, 1, 2, 2, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_custom_symbols() {
+        let input = lines_to_grid(
+            &FIXTURE
+                .replace('@', "#")
+                .replace('.', " ")
+                .lines()
+                .collect::<Vec<_>>(),
+        );
+        let rules = Rules {
+            roll: '#',
+            empty: ' ',
+            ..Rules::default()
+        };
+        assert_eq!(part1(&input, &rules), 13);
+        assert_eq!(part2(&erode(&input, &rules)), 43);
+
+        let rules = Rules {
+            neighbourhood: Neighbourhood::VonNeumann,
+            max_neighbours: 1,
+            roll: '#',
+            ..Rules::default()
+        };
+        assert_eq!(part1(&input, &rules), 11);
+        assert_eq!(part2(&erode(&input, &rules)), 16);
     }
 }
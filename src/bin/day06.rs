@@ -1,69 +1,573 @@
-use aoc2025_rs::read_lines;
+use std::collections::HashMap;
 
-fn part1<S: AsRef<str>>(lines: &[S]) -> u64 {
-    let len = lines.len();
+use aoc2025_rs::{arg_value, bignum::BigUint, has_flag, read_lines};
 
-    let pieces: Vec<_> = lines
-        .iter()
-        .map(|line| line.as_ref().split_whitespace().collect::<Vec<_>>())
-        .collect();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fold {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    Overflow,
+    Undefined,
+}
+
+type Apply = fn(u128, u128) -> Result<u128, Failure>;
+type BigApply = fn(&BigUint, &BigUint) -> BigUint;
+
+fn fold_with<T: Clone>(
+    fold: Fold,
+    operands: &[T],
+    apply: impl Fn(&T, &T) -> Result<T, Failure>,
+) -> Result<T, Failure> {
+    match fold {
+        Fold::Left => {
+            let (first, rest) = operands.split_first().ok_or(Failure::Undefined)?;
+            rest.iter().try_fold(first.clone(), |acc, x| apply(&acc, x))
+        }
+        Fold::Right => {
+            let (last, rest) = operands.split_last().ok_or(Failure::Undefined)?;
+            rest.iter()
+                .rev()
+                .try_fold(last.clone(), |acc, x| apply(x, &acc))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Small(u128),
+    Big(BigUint),
+}
+
+impl Value {
+    fn from_big(n: BigUint) -> Self {
+        match n.to_u128() {
+            Some(n) => Value::Small(n),
+            None => Value::Big(n),
+        }
+    }
+
+    fn to_big(&self) -> BigUint {
+        match self {
+            Value::Small(n) => BigUint::from(*n),
+            Value::Big(n) => n.clone(),
+        }
+    }
+}
+
+impl From<u128> for Value {
+    fn from(n: u128) -> Self {
+        Value::Small(n)
+    }
+}
 
-    let ops = &pieces[len - 1];
+impl std::ops::Add for &Value {
+    type Output = Value;
 
-    let nums: Vec<Vec<u64>> = pieces[..len - 1]
-        .iter()
-        .map(|ns| ns.iter().map(|n| n.parse::<u64>().unwrap()).collect())
-        .collect();
+    fn add(self, other: &Value) -> Value {
+        match (self, other) {
+            (Value::Small(a), Value::Small(b)) => match a.checked_add(*b) {
+                Some(sum) => Value::Small(sum),
+                None => Value::from_big(&self.to_big() + &other.to_big()),
+            },
+            _ => Value::from_big(&self.to_big() + &other.to_big()),
+        }
+    }
+}
 
-    let mut sum = 0;
-    for (i, &op) in ops.iter().enumerate() {
-        let col = nums.iter().map(|line| line[i]);
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Small(n) => write!(f, "{}", n),
+            Value::Big(n) => write!(f, "{}", n),
+        }
+    }
+}
 
-        match op {
-            "+" => sum += col.sum::<u64>(),
-            "*" => sum += col.product::<u64>(),
-            _ => panic!("Unknown operator"),
+#[derive(Clone, Copy)]
+struct Operator {
+    fold: Fold,
+    apply: Apply,
+    big: Option<BigApply>,
+}
+
+impl Operator {
+    // Results that overflow u128 are recomputed with `big` when the operator
+    // has one, and reported as an overflow otherwise.
+    fn evaluate(&self, operands: &[u128]) -> Result<Value, Failure> {
+        let small = fold_with(self.fold, operands, |a, b| (self.apply)(*a, *b));
+
+        match (small, self.big) {
+            (Ok(n), _) => Ok(Value::Small(n)),
+            (Err(Failure::Overflow), Some(big)) => {
+                let operands: Vec<BigUint> = operands.iter().map(|&n| n.into()).collect();
+                fold_with(self.fold, &operands, |a, b| Ok(big(a, b))).map(Value::from_big)
+            }
+            (Err(failure), _) => Err(failure),
         }
     }
+}
 
-    sum
+struct Operators {
+    table: HashMap<String, Operator>,
+}
+
+impl Default for Operators {
+    fn default() -> Self {
+        let mut operators = Self {
+            table: HashMap::new(),
+        };
+
+        operators.register_with_fallback(
+            "+",
+            Fold::Left,
+            |a, b| a.checked_add(b).ok_or(Failure::Overflow),
+            |a, b| a + b,
+        );
+        operators.register_with_fallback(
+            "*",
+            Fold::Left,
+            |a, b| a.checked_mul(b).ok_or(Failure::Overflow),
+            |a, b| a * b,
+        );
+        operators.register("-", Fold::Left, |a, b| {
+            a.checked_sub(b).ok_or(Failure::Undefined)
+        });
+        operators.register("/", Fold::Left, |a, b| {
+            a.checked_div(b).ok_or(Failure::Undefined)
+        });
+        operators.register("%", Fold::Left, |a, b| {
+            a.checked_rem(b).ok_or(Failure::Undefined)
+        });
+        operators.register("min", Fold::Left, |a, b| Ok(a.min(b)));
+        operators.register("max", Fold::Left, |a, b| Ok(a.max(b)));
+        operators.register("^", Fold::Left, |a, b| Ok(a ^ b));
+        operators.register("**", Fold::Right, |a, b| {
+            let exp = b.try_into().map_err(|_| Failure::Overflow)?;
+            a.checked_pow(exp).ok_or(Failure::Overflow)
+        });
+        operators
+    }
+}
+
+impl Operators {
+    fn register(&mut self, symbol: &str, fold: Fold, apply: Apply) {
+        self.table.insert(
+            symbol.to_string(),
+            Operator {
+                fold,
+                apply,
+                big: None,
+            },
+        );
+    }
+
+    fn register_with_fallback(&mut self, symbol: &str, fold: Fold, apply: Apply, big: BigApply) {
+        self.table.insert(
+            symbol.to_string(),
+            Operator {
+                fold,
+                apply,
+                big: Some(big),
+            },
+        );
+    }
+
+    fn get(&self, symbol: &str) -> Option<Operator> {
+        self.table.get(symbol).copied()
+    }
 }
 
-fn part2<S: AsRef<str>>(lines: &[S]) -> u64 {
-    let rows = lines.len();
-    let n = rows - 1;
-    let cols = lines[0].as_ref().len();
+#[derive(Debug, PartialEq, Eq)]
+struct WorksheetError {
+    line: usize,
+    column: usize,
+    reason: &'static str,
+}
 
-    let ops = lines[n].as_ref().split_whitespace().collect::<Vec<_>>();
-    let chars = lines[..n]
-        .iter()
-        .map(|line| line.as_ref().chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
+impl WorksheetError {
+    fn new(line: usize, column: usize, reason: &'static str) -> Self {
+        Self {
+            line,
+            column,
+            reason,
+        }
+    }
+}
 
-    let transposed: Vec<_> = (0..cols)
-        .map(|col| (0..n).map(|row| chars[row][col]).collect::<Vec<_>>())
-        .map(|num| num.iter().collect::<String>())
-        .collect();
+impl std::fmt::Display for WorksheetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.reason
+        )
+    }
+}
 
-    let grouped: Vec<Vec<u64>> = transposed
-        .split(|s| s.trim().is_empty())
-        .map(|group| group.iter().map(|s| s.trim().parse().unwrap()).collect())
-        .collect();
+struct Problem {
+    line: usize,
+    column: usize,
+    operator: Operator,
+    operands: Vec<u128>,
+}
 
-    ops.iter()
-        .zip(grouped.iter())
-        .map(|(&op, group)| match op {
-            "+" => group.iter().sum::<u64>(),
-            "*" => group.iter().product::<u64>(),
-            _ => panic!("Unknown operator"),
+impl Problem {
+    fn evaluate(&self) -> Result<Value, WorksheetError> {
+        self.operator.evaluate(&self.operands).map_err(|failure| {
+            let reason = match failure {
+                Failure::Overflow => "overflow",
+                Failure::Undefined => "undefined result",
+            };
+            WorksheetError::new(self.line, self.column, reason)
         })
-        .sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Orientation {
+    Rows,
+    ColumnsTopDown,
+    ColumnsBottomUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Direction {
+    fn columns(self, range: std::ops::Range<usize>) -> Box<dyn Iterator<Item = usize>> {
+        match self {
+            Direction::LeftToRight => Box::new(range),
+            Direction::RightToLeft => Box::new(range.rev()),
+        }
+    }
+}
+
+// Direction orders the problems and, when reading columns, the operands
+// within each problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reading {
+    orientation: Orientation,
+    direction: Direction,
+}
+
+const PART1: Reading = Reading {
+    orientation: Orientation::Rows,
+    direction: Direction::LeftToRight,
+};
+
+const PART2: Reading = Reading {
+    orientation: Orientation::ColumnsTopDown,
+    direction: Direction::RightToLeft,
+};
+
+// Tokens of a row with their 0-based starting column.
+fn tokens(row: &[char]) -> Vec<(usize, String)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, &c) in row.iter().chain([&' ']).enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                tokens.push((s, row[s..i].iter().collect()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+struct Block {
+    start: usize,
+    end: usize,
+    column: usize,
+    operator: Operator,
+}
+
+// Lines are padded to a common width, so `rows` can be indexed by any column
+// of the worksheet. Line numbers are kept alongside each row for errors.
+struct Worksheet {
+    operator_line: usize,
+    rows: Vec<(usize, Vec<char>)>,
+    blocks: Vec<Block>,
+}
+
+impl Worksheet {
+    fn parse<S: AsRef<str>>(lines: &[S], operators: &Operators) -> Result<Self, WorksheetError> {
+        let mut rows: Vec<(usize, Vec<char>)> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.as_ref().trim().is_empty())
+            .map(|(i, line)| (i + 1, line.as_ref().chars().collect()))
+            .collect();
+
+        let width = rows.iter().map(|(_, row)| row.len()).max().unwrap_or(0);
+        for (_, row) in rows.iter_mut() {
+            row.resize(width, ' ');
+        }
+
+        let mut operator_rows = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, row))| !row.iter().any(char::is_ascii_digit));
+        let (operator_index, operator_line) = match (operator_rows.next(), operator_rows.next()) {
+            (Some((i, &(line, _))), None) => (i, line),
+            (None, _) => return Err(WorksheetError::new(1, 1, "missing operator row")),
+            (_, Some((_, &(line, _)))) => {
+                return Err(WorksheetError::new(line, 1, "multiple operator rows"));
+            }
+        };
+        let operator_row = rows.remove(operator_index).1;
+
+        for (line, row) in &rows {
+            if let Some(col) = row
+                .iter()
+                .position(|c| !c.is_ascii_digit() && !c.is_whitespace())
+            {
+                return Err(WorksheetError::new(*line, col + 1, "invalid digit"));
+            }
+        }
+
+        // Problems are separated by columns that are blank in every number row.
+        // Operators may be wider than their numbers, so each operator belongs
+        // to the last problem starting at or before it.
+        let blank: Vec<bool> = (0..width)
+            .map(|col| rows.iter().all(|(_, row)| row[col] == ' '))
+            .collect();
+
+        let mut ranges = Vec::new();
+        let mut col = 0;
+
+        while col < width {
+            if blank[col] {
+                col += 1;
+                continue;
+            }
+
+            let start = col;
+            while col < width && !blank[col] {
+                col += 1;
+            }
+            ranges.push((start, col));
+        }
+
+        let mut symbols: Vec<Option<(usize, String)>> = vec![None; ranges.len()];
+
+        for (offset, symbol) in tokens(&operator_row) {
+            let i = ranges
+                .iter()
+                .rposition(|&(start, _)| start <= offset)
+                .ok_or(WorksheetError::new(
+                    operator_line,
+                    offset + 1,
+                    "operator outside a problem",
+                ))?;
+
+            if symbols[i].is_some() {
+                return Err(WorksheetError::new(
+                    operator_line,
+                    offset + 1,
+                    "multiple operators",
+                ));
+            }
+            symbols[i] = Some((offset, symbol));
+        }
+
+        let blocks = ranges
+            .into_iter()
+            .zip(symbols)
+            .map(|((start, end), symbol)| {
+                let (offset, symbol) = symbol.ok_or(WorksheetError::new(
+                    operator_line,
+                    start + 1,
+                    "missing operator",
+                ))?;
+
+                let column = offset + 1;
+                let operator = operators.get(&symbol).ok_or(WorksheetError::new(
+                    operator_line,
+                    column,
+                    "unknown operator",
+                ))?;
+
+                Ok(Block {
+                    start,
+                    end,
+                    column,
+                    operator,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            operator_line,
+            rows,
+            blocks,
+        })
+    }
+
+    fn problem(&self, block: &Block, operands: Vec<u128>) -> Problem {
+        Problem {
+            line: self.operator_line,
+            column: block.column,
+            operator: block.operator,
+            operands,
+        }
+    }
+
+    fn row_operands(&self, block: &Block) -> Result<Vec<u128>, WorksheetError> {
+        let mut operands = Vec::new();
+
+        for (line, row) in &self.rows {
+            match tokens(&row[block.start..block.end]).as_slice() {
+                [] => {}
+                [(offset, number)] => {
+                    operands.push(parse_number(number, *line, block.start + offset + 1)?)
+                }
+                [_, (offset, _), ..] => {
+                    return Err(WorksheetError::new(
+                        *line,
+                        block.start + offset + 1,
+                        "multiple numbers in one problem row",
+                    ));
+                }
+            }
+        }
+
+        Ok(operands)
+    }
+
+    fn column_operands(
+        &self,
+        block: &Block,
+        orientation: Orientation,
+        direction: Direction,
+    ) -> Result<Vec<u128>, WorksheetError> {
+        let mut operands = Vec::new();
+        let line = self.rows.first().map_or(1, |&(line, _)| line);
+
+        for col in direction.columns(block.start..block.end) {
+            let mut digits: String = self
+                .rows
+                .iter()
+                .map(|(_, row)| row[col])
+                .filter(char::is_ascii_digit)
+                .collect();
+
+            if orientation == Orientation::ColumnsBottomUp {
+                digits = digits.chars().rev().collect();
+            }
+
+            if !digits.is_empty() {
+                operands.push(parse_number(&digits, line, col + 1)?);
+            }
+        }
+
+        Ok(operands)
+    }
+
+    fn read(&self, reading: Reading) -> Result<Vec<Problem>, WorksheetError> {
+        let mut blocks: Vec<&Block> = self.blocks.iter().collect();
+        if reading.direction == Direction::RightToLeft {
+            blocks.reverse();
+        }
+
+        blocks
+            .into_iter()
+            .map(|block| {
+                let operands = match reading.orientation {
+                    Orientation::Rows => self.row_operands(block)?,
+                    orientation => self.column_operands(block, orientation, reading.direction)?,
+                };
+                Ok(self.problem(block, operands))
+            })
+            .collect()
+    }
+}
+
+fn parse_number(digits: &str, line: usize, column: usize) -> Result<u128, WorksheetError> {
+    digits
+        .parse()
+        .map_err(|_| WorksheetError::new(line, column, "number too large"))
+}
+
+fn evaluate(problems: &[Problem]) -> Result<Value, WorksheetError> {
+    problems.iter().try_fold(Value::Small(0), |total, problem| {
+        Ok(&total + &problem.evaluate()?)
+    })
+}
+
+fn read<S: AsRef<str>>(
+    lines: &[S],
+    operators: &Operators,
+    reading: Reading,
+) -> Result<Vec<Problem>, WorksheetError> {
+    Worksheet::parse(lines, operators)?.read(reading)
+}
+
+fn solve<S: AsRef<str>>(
+    lines: &[S],
+    operators: &Operators,
+    reading: Reading,
+) -> Result<Value, WorksheetError> {
+    evaluate(&read(lines, operators, reading)?)
+}
+
+fn part1<S: AsRef<str>>(lines: &[S], operators: &Operators) -> Result<Value, WorksheetError> {
+    solve(lines, operators, PART1)
+}
+
+fn part2<S: AsRef<str>>(lines: &[S], operators: &Operators) -> Result<Value, WorksheetError> {
+    solve(lines, operators, PART2)
+}
+
+fn reading_from_args() -> Option<Reading> {
+    let orientation = arg_value("--orientation").map(|o| match o.as_str() {
+        "rows" => Orientation::Rows,
+        "top-down" => Orientation::ColumnsTopDown,
+        "bottom-up" => Orientation::ColumnsBottomUp,
+        _ => panic!("Unknown orientation {}", o),
+    });
+    let direction = has_flag("--right-to-left").then_some(Direction::RightToLeft);
+
+    if orientation.is_none() && direction.is_none() {
+        return None;
+    }
+
+    Some(Reading {
+        orientation: orientation.unwrap_or(Orientation::Rows),
+        direction: direction.unwrap_or(Direction::LeftToRight),
+    })
 }
 
 fn main() {
     let lines = read_lines(6);
-    println!("Part 1: {}", part1(&lines));
-    println!("Part 2: {}", part2(&lines));
+    let operators = Operators::default();
+
+    match part1(&lines, &operators) {
+        Ok(answer) => println!("Part 1: {}", answer),
+        Err(e) => println!("Part 1: {}", e),
+    }
+    match part2(&lines, &operators) {
+        Ok(answer) => println!("Part 2: {}", answer),
+        Err(e) => println!("Part 2: {}", e),
+    }
+
+    if let Some(reading) = reading_from_args() {
+        match solve(&lines, &operators, reading) {
+            Ok(answer) => println!("{:?}: {}", reading, answer),
+            Err(e) => println!("{:?}: {}", reading, e),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -79,12 +583,191 @@ mod test {
     #[test]
     fn test_part1() {
         let lines = FIXTURE.lines().collect::<Vec<_>>();
-        assert_eq!(part1(&lines), 4277556);
+        assert_eq!(part1(&lines, &Operators::default()), Ok(4277556.into()));
     }
 
     #[test]
     fn test_part2() {
         let lines = FIXTURE.lines().collect::<Vec<_>>();
-        assert_eq!(part2(&lines), 3263827);
+        assert_eq!(part2(&lines, &Operators::default()), Ok(3263827.into()));
+    }
+
+    #[test]
+    fn test_operators() {
+        let lines = [
+            "20 7 100 12  9   6",
+            "6  2 3   30  4   3",
+            "-  / %   min max ^",
+        ];
+        let operators = Operators::default();
+        let results: Vec<Value> = read(&lines, &operators, PART1)
+            .unwrap()
+            .iter()
+            .map(|problem| problem.evaluate().unwrap())
+            .collect();
+        assert_eq!(results, [14, 3, 1, 12, 9, 5].map(Value::from));
+    }
+
+    #[test]
+    fn test_fold_direction() {
+        let mut operators = Operators::default();
+        operators.register("<-", Fold::Right, |a, b| {
+            a.checked_sub(b).ok_or(Failure::Undefined)
+        });
+
+        let lines = ["10 2", "4  3", "3  2", "<- **"];
+        let results: Vec<Value> = read(&lines, &operators, PART1)
+            .unwrap()
+            .iter()
+            .map(|problem| problem.evaluate().unwrap())
+            .collect();
+        assert_eq!(results, [9, 512].map(Value::from));
+    }
+
+    #[test]
+    fn test_errors() {
+        let operators = Operators::default();
+        assert_eq!(
+            read(&["1 2 3", "4 5 6", "+ ? *"], &operators, PART1).err(),
+            Some(WorksheetError::new(3, 3, "unknown operator"))
+        );
+        assert_eq!(
+            part1(&["1 2", "0 5", "/ -"], &operators),
+            Err(WorksheetError::new(3, 1, "undefined result"))
+        );
+        assert_eq!(
+            part1(&["1 2", "0 5", "+ -"], &operators),
+            Err(WorksheetError::new(3, 3, "undefined result"))
+        );
+    }
+
+    #[test]
+    fn test_ragged_layout() {
+        let operators = Operators::default();
+        let lines = [
+            "",
+            "*   +   *   +",
+            "123 328  51 64",
+            " 45 64  387 23",
+            "  6 98  215 314",
+            "",
+        ];
+        assert_eq!(part1(&lines, &operators), Ok(4277556.into()));
+        assert_eq!(part2(&lines, &operators), Ok(3263827.into()));
+
+        let trimmed: Vec<&str> = FIXTURE.lines().map(str::trim_end).collect();
+        assert_eq!(part1(&trimmed, &operators), Ok(4277556.into()));
+        assert_eq!(part2(&trimmed, &operators), Ok(3263827.into()));
+
+        let wide = ["1 2", "3 4", "min +"];
+        assert_eq!(part1(&wide, &operators), Ok(7.into()));
+        assert_eq!(part2(&wide, &operators), Ok(37.into()));
+    }
+
+    #[test]
+    fn test_malformed_blocks() {
+        let operators = Operators::default();
+        let cases = [
+            (
+                vec!["1 2", "3 4"],
+                WorksheetError::new(1, 1, "missing operator row"),
+            ),
+            (
+                vec!["1 2", "+ *", "- /"],
+                WorksheetError::new(3, 1, "multiple operator rows"),
+            ),
+            (
+                vec!["1 2", "3 x", "+ *"],
+                WorksheetError::new(2, 3, "invalid digit"),
+            ),
+            (
+                vec!["12 3", "4  5", "+"],
+                WorksheetError::new(3, 4, "missing operator"),
+            ),
+            (
+                vec!["123", "+ *"],
+                WorksheetError::new(2, 3, "multiple operators"),
+            ),
+            (
+                vec!["  1", "+ *"],
+                WorksheetError::new(2, 1, "operator outside a problem"),
+            ),
+        ];
+
+        for (lines, error) in cases {
+            assert_eq!(read(&lines, &operators, PART1).err(), Some(error));
+        }
+
+        assert_eq!(
+            read(&["1 2", " 3", "+"], &operators, PART1).err(),
+            Some(WorksheetError::new(
+                1,
+                3,
+                "multiple numbers in one problem row"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_wide_products() {
+        let operators = Operators::default();
+        let mut lines = vec!["9999 1"; 12];
+        lines.push("*    +");
+
+        let total = part1(&lines, &operators).unwrap();
+        assert!(matches!(total, Value::Big(_)));
+        assert_eq!(
+            total.to_string(),
+            "998800659780049492080923920804949780006599880013"
+        );
+
+        assert_eq!(
+            part1(&["2   9", "200 300", "+   **"], &operators),
+            Err(WorksheetError::new(3, 5, "overflow"))
+        );
+    }
+
+    #[test]
+    fn test_orientations() {
+        use Direction::*;
+        use Orientation::*;
+
+        let operators = Operators::default();
+        let lines = ["53 2", "21 3", "-  **"];
+        let results = |orientation, direction| -> Vec<Result<Value, WorksheetError>> {
+            let reading = Reading {
+                orientation,
+                direction,
+            };
+            read(&lines, &operators, reading)
+                .unwrap()
+                .iter()
+                .map(Problem::evaluate)
+                .collect()
+        };
+
+        assert_eq!(
+            results(Rows, LeftToRight),
+            vec![Ok(32.into()), Ok(8.into())]
+        );
+        assert_eq!(
+            results(Rows, RightToLeft),
+            vec![Ok(8.into()), Ok(32.into())]
+        );
+        assert_eq!(
+            results(ColumnsTopDown, LeftToRight),
+            vec![Ok(21.into()), Ok(23.into())]
+        );
+        assert_eq!(
+            results(ColumnsBottomUp, LeftToRight),
+            vec![Ok(12.into()), Ok(32.into())]
+        );
+        assert_eq!(
+            results(ColumnsTopDown, RightToLeft),
+            vec![
+                Ok(23.into()),
+                Err(WorksheetError::new(3, 1, "undefined result"))
+            ]
+        );
     }
 }
@@ -1,80 +1,230 @@
-use std::{
-    collections::{HashMap, HashSet},
-    time::Instant,
-};
+use std::time::Instant;
 
-use aoc2025_rs::read_lines;
+use aoc2025_rs::{arg_value, read_lines};
 
-fn parse<S: AsRef<str>>(lines: &[S]) -> Vec<Vec<usize>> {
-    lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    Drop,
+    Wrap,
+    Count,
+}
+
+// One mask per kind of cell. Splitters `^` and duplicators `v` send a beam
+// both ways (a duplicator also keeps it), deflectors `/` and `\\` move it one
+// column west or east, and absorbers `#` stop it.
+struct Row {
+    sources: Vec<u64>,
+    splitters: Vec<u64>,
+    duplicators: Vec<u64>,
+    west: Vec<u64>,
+    east: Vec<u64>,
+    absorbers: Vec<u64>,
+}
+
+impl Row {
+    fn parse(line: &str, width: usize) -> Self {
+        Self {
+            sources: mask(line, 'S', width),
+            splitters: mask(line, '^', width),
+            duplicators: mask(line, 'v', width),
+            west: mask(line, '/', width),
+            east: mask(line, '\\', width),
+            absorbers: mask(line, '#', width),
+        }
+    }
+
+    // Whether a beam at `col` continues west, straight down and east.
+    fn moves(&self, col: usize) -> [bool; 3] {
+        let split = is_set(&self.splitters, col);
+        let duplicate = is_set(&self.duplicators, col);
+
+        [
+            split || duplicate || is_set(&self.west, col),
+            duplicate
+                || !(split
+                    || is_set(&self.west, col)
+                    || is_set(&self.east, col)
+                    || is_set(&self.absorbers, col)),
+            split || duplicate || is_set(&self.east, col),
+        ]
+    }
+}
+
+struct Manifold {
+    width: usize,
+    rows: Vec<Row>,
+}
+
+fn mask(line: &str, symbol: char, width: usize) -> Vec<u64> {
+    let mut mask = vec![0; width.div_ceil(64)];
+    for (i, _) in line.char_indices().filter(|&(_, c)| c == symbol) {
+        mask[i / 64] |= 1 << (i % 64);
+    }
+    mask
+}
+
+fn parse<S: AsRef<str>>(lines: &[S]) -> Manifold {
+    let width = lines
+        .iter()
+        .map(|line| line.as_ref().len())
+        .max()
+        .unwrap_or(0);
+
+    let rows: Vec<Row> = lines
         .iter()
-        .map(|line| {
-            line.as_ref()
-                .char_indices()
-                .filter(|(_, c)| *c == 'S' || *c == '^')
-                .map(|(i, _)| i)
-                .collect()
-        })
-        .collect()
+        .map(|line| Row::parse(line.as_ref(), width))
+        .collect();
+
+    assert!(
+        rows.iter().flat_map(|row| &row.sources).any(|&w| w != 0),
+        "No source in manifold"
+    );
+
+    Manifold { width, rows }
+}
+
+// Bit i of a mask is column i, so moving a beam east shifts towards the high
+// bits and carries across word boundaries.
+fn move_east(mask: &[u64], i: usize) -> u64 {
+    let carry = if i > 0 { mask[i - 1] >> 63 } else { 0 };
+    (mask[i] << 1) | carry
+}
+
+fn move_west(mask: &[u64], i: usize) -> u64 {
+    let carry = mask.get(i + 1).map_or(0, |w| w << 63);
+    (mask[i] >> 1) | carry
+}
+
+fn is_set(mask: &[u64], col: usize) -> bool {
+    mask[col / 64] >> (col % 64) & 1 == 1
+}
+
+fn set(mask: &mut [u64], col: usize) {
+    mask[col / 64] |= 1 << (col % 64);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Beams {
+    splits: usize,
+    timelines: u64,
+    exited: u64,
 }
 
-fn part1(grid: &[Vec<usize>]) -> usize {
-    let mut active = HashSet::from([grid[0][0]]);
+fn propagate(manifold: &Manifold, exit: Exit) -> Beams {
+    let width = manifold.width;
+    let words = width.div_ceil(64);
+    let last_word = match width % 64 {
+        0 => u64::MAX,
+        bits => (1 << bits) - 1,
+    };
+
+    // Column a beam moved sideways from `col` lands in, if it stays on the grid.
+    let target = |col: usize, east: bool| -> Option<usize> {
+        match (east, exit) {
+            (false, Exit::Wrap) => Some(col.checked_sub(1).unwrap_or(width - 1)),
+            (true, Exit::Wrap) => Some((col + 1) % width),
+            (false, _) => col.checked_sub(1),
+            (true, _) => Some(col + 1).filter(|&c| c < width),
+        }
+    };
+
+    let mut active = vec![0; words];
+    let mut counts = vec![0u64; width];
+    let mut next = vec![0u64; width];
     let mut splits = 0;
+    let mut exited = 0;
+
+    for row in &manifold.rows {
+        let mut westward = vec![0; words];
+        let mut eastward = vec![0; words];
 
-    for splitters in grid[1..].iter() {
-        for splitter in splitters.iter() {
-            if active.contains(splitter) {
-                active.remove(splitter);
-                active.insert(splitter - 1);
-                active.insert(splitter + 1);
+        for i in 0..words {
+            let split = row.splitters[i] | row.duplicators[i];
+            splits += (active[i] & split).count_ones() as usize;
 
-                splits += 1;
+            westward[i] = active[i] & (split | row.west[i]);
+            eastward[i] = active[i] & (split | row.east[i]);
+            active[i] &= !(row.splitters[i] | row.west[i] | row.east[i] | row.absorbers[i]);
+        }
+
+        for (i, word) in active.iter_mut().enumerate() {
+            *word |= move_west(&westward, i) | move_east(&eastward, i);
+        }
+        active[words - 1] &= last_word;
+
+        if exit == Exit::Wrap {
+            if is_set(&westward, 0) {
+                set(&mut active, width - 1);
+            }
+            if is_set(&eastward, width - 1) {
+                set(&mut active, 0);
             }
         }
-    }
 
-    splits
-}
+        next.fill(0);
+        for (col, &count) in counts.iter().enumerate().filter(|&(_, &c)| c > 0) {
+            let [west, down, east] = row.moves(col);
 
-fn part2(grid: &[Vec<usize>]) -> usize {
-    let mut active = HashMap::from([(grid[0][0], 1)]);
+            if down {
+                next[col] += count;
+            }
 
-    for splitters in grid[1..].iter() {
-        let mut next_state: HashMap<usize, usize> = HashMap::new();
+            for (moves, east) in [(west, false), (east, true)] {
+                if !moves {
+                    continue;
+                }
 
-        for (&col, &count) in &active {
-            if splitters.contains(&col) {
-                *next_state.entry(col - 1).or_default() += count;
-                *next_state.entry(col + 1).or_default() += count;
-            } else {
-                *next_state.entry(col).or_default() += count;
+                match target(col, east) {
+                    Some(c) => next[c] += count,
+                    None if exit == Exit::Count => exited += count,
+                    None => {}
+                }
             }
         }
+        std::mem::swap(&mut counts, &mut next);
 
-        active = next_state;
+        for col in (0..width).filter(|&col| is_set(&row.sources, col)) {
+            set(&mut active, col);
+            counts[col] += 1;
+        }
     }
 
-    active.values().sum()
+    Beams {
+        splits,
+        timelines: counts.iter().sum(),
+        exited,
+    }
 }
 
 fn main() {
-    let grid = parse(&read_lines(7));
-    println!("Part 1: {}", part1(&grid));
+    let manifold = parse(&read_lines(7));
+    let exit = match arg_value("--exit").as_deref() {
+        None | Some("drop") => Exit::Drop,
+        Some("wrap") => Exit::Wrap,
+        Some("count") => Exit::Count,
+        Some(other) => panic!("Unknown exit policy {}", other),
+    };
 
     let now = Instant::now();
-    let p2 = part2(&grid);
+    let beams = propagate(&manifold, exit);
     let elapsed = now.elapsed();
 
+    println!("Part 1: {}", beams.splits);
     println!(
         "Part 2: {} ({}ms)",
-        p2,
+        beams.timelines,
         (elapsed.as_micros() as f64) / 1000.0
     );
+
+    if exit == Exit::Count {
+        println!("Exited: {}", beams.exited);
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
 
     const FIXTURE: &str = ".......S.......
@@ -94,15 +244,134 @@ mod tests {
 .^.^.^.^.^...^.
 ...............";
 
+    fn propagate_sparse(lines: &[String]) -> Beams {
+        let source = lines[0].find('S').unwrap();
+        let mut active = HashMap::from([(source, 1)]);
+        let mut splits = 0;
+
+        for line in &lines[1..] {
+            let mut next: HashMap<usize, u64> = HashMap::new();
+
+            for (&col, &count) in &active {
+                if line.as_bytes()[col] == b'^' {
+                    *next.entry(col - 1).or_default() += count;
+                    *next.entry(col + 1).or_default() += count;
+                    splits += 1;
+                } else {
+                    *next.entry(col).or_default() += count;
+                }
+            }
+
+            active = next;
+        }
+
+        Beams {
+            splits,
+            timelines: active.values().sum(),
+            exited: 0,
+        }
+    }
+
     #[test]
     fn test_part1() {
-        let grid = parse(&FIXTURE.lines().collect::<Vec<_>>());
-        assert_eq!(part1(&grid), 21);
+        let manifold = parse(&FIXTURE.lines().collect::<Vec<_>>());
+        assert_eq!(propagate(&manifold, Exit::Drop).splits, 21);
     }
 
     #[test]
     fn test_part2() {
-        let grid = parse(&FIXTURE.lines().collect::<Vec<_>>());
-        assert_eq!(part2(&grid), 40);
+        let manifold = parse(&FIXTURE.lines().collect::<Vec<_>>());
+        assert_eq!(propagate(&manifold, Exit::Drop).timelines, 40);
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let width = 200;
+        let mut state: u32 = 7;
+        let mut lines = vec![format!("{}S{}", ".".repeat(63), ".".repeat(width - 64))];
+
+        for _ in 0..100 {
+            let row: String = (0..width)
+                .map(|col| {
+                    state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                    let inside = col > 0 && col < width - 1;
+                    if inside && (state >> 16).is_multiple_of(4) {
+                        '^'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            lines.push(row);
+        }
+
+        let manifold = parse(&lines);
+        assert_eq!(propagate(&manifold, Exit::Drop), propagate_sparse(&lines));
+    }
+
+    #[test]
+    fn test_exit_policies() {
+        let manifold = parse(&["S...S", "^...^", ".....", "^...^"]);
+        let cases = [
+            (Exit::Drop, 2, 2, 0),
+            (Exit::Count, 2, 2, 2),
+            (Exit::Wrap, 4, 6, 0),
+        ];
+
+        for (exit, splits, timelines, exited) in cases {
+            assert_eq!(
+                propagate(&manifold, exit),
+                Beams {
+                    splits,
+                    timelines,
+                    exited
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiple_sources() {
+        let manifold = parse(&["..S..", "..S..", "..^.S", "....."]);
+        assert_eq!(
+            propagate(&manifold, Exit::Drop),
+            Beams {
+                splits: 1,
+                timelines: 5,
+                exited: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_elements() {
+        let manifold = parse(&["..S..", "../..", ".....", ".v...", ".....", "#.\\.."]);
+        let beams = propagate(&manifold, Exit::Drop);
+        assert_eq!(
+            beams,
+            Beams {
+                splits: 1,
+                timelines: 2,
+                exited: 0
+            }
+        );
+
+        let manifold = parse(&["S.S.S", "/.v.\\", "....."]);
+        let cases = [
+            (Exit::Drop, 1, 3, 0),
+            (Exit::Count, 1, 3, 2),
+            (Exit::Wrap, 1, 5, 0),
+        ];
+
+        for (exit, splits, timelines, exited) in cases {
+            assert_eq!(
+                propagate(&manifold, exit),
+                Beams {
+                    splits,
+                    timelines,
+                    exited
+                }
+            );
+        }
     }
 }
@@ -1,47 +1,225 @@
-use std::str::FromStr;
+use std::{fmt::Write, str::FromStr};
 
-use aoc2025_rs::{read_lines, timed};
+use aoc2025_rs::{arg_value, read_lines, timed};
 use good_lp::{
     Expression, ProblemVariables, Solution, SolverModel, Variable, constraint, default_solver,
     variable,
 };
+use rayon::{ThreadPoolBuilder, prelude::*};
+
+const MAX_LIGHTS: usize = 16;
+const MAX_BUTTONS: usize = 63;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Machine {
+    lights: Vec<bool>,
+    buttons: Vec<Vec<u16>>,
+    weights: Vec<u32>,
+    joltages: Vec<u16>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ParseMachineError {
+    column: usize,
+    reason: &'static str,
+}
+
+impl ParseMachineError {
+    fn new(column: usize, reason: &'static str) -> Self {
+        Self { column, reason }
+    }
+}
+
+impl std::fmt::Display for ParseMachineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "column {}: {}", self.column, self.reason)
+    }
+}
+
+fn parse_list<T: FromStr>(
+    list: &str,
+    column: usize,
+    reason: &'static str,
+) -> Result<Vec<(usize, T)>, ParseMachineError> {
+    let mut offset = 0;
+
+    list.split(',')
+        .map(|n| {
+            let item_column = column + offset;
+            offset += n.len() + 1;
+
+            n.parse()
+                .map(|value| (item_column, value))
+                .map_err(|_| ParseMachineError::new(item_column, reason))
+        })
+        .collect()
+}
+
+impl FromStr for Machine {
+    type Err = ParseMachineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s
+            .split_whitespace()
+            .map(|token| (token.as_ptr() as usize - s.as_ptr() as usize + 1, token));
+
+        let (column, diagram) = tokens
+            .next()
+            .ok_or(ParseMachineError::new(1, "missing indicator diagram"))?;
+
+        let diagram = diagram
+            .strip_prefix('[')
+            .and_then(|d| d.strip_suffix(']'))
+            .ok_or(ParseMachineError::new(
+                column,
+                "expected [indicator diagram]",
+            ))?;
+
+        let lights = diagram
+            .char_indices()
+            .map(|(i, c)| match c {
+                '#' => Ok(true),
+                '.' => Ok(false),
+                _ => Err(ParseMachineError::new(
+                    column + 1 + i,
+                    "expected '.' or '#'",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if lights.len() > MAX_LIGHTS {
+            return Err(ParseMachineError::new(column, "too many lights"));
+        }
+
+        let mut buttons = Vec::new();
+        let mut weights = Vec::new();
+        let mut joltages = None;
+
+        for (column, token) in tokens {
+            if joltages.is_some() {
+                return Err(ParseMachineError::new(
+                    column,
+                    "unexpected token after joltages",
+                ));
+            }
+
+            if let Some(list) = token.strip_prefix('{') {
+                if buttons.is_empty() {
+                    return Err(ParseMachineError::new(column, "missing button wirings"));
+                }
+
+                let list = list
+                    .strip_suffix('}')
+                    .ok_or(ParseMachineError::new(column, "unclosed joltages"))?;
+                let values: Vec<u16> = parse_list(list, column + 1, "invalid joltage")?
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect();
+
+                if values.len() != lights.len() {
+                    return Err(ParseMachineError::new(
+                        column,
+                        "joltage count does not match light count",
+                    ));
+                }
+
+                joltages = Some(values);
+                continue;
+            }
+
+            let (wiring, weight) = match token.split_once(':') {
+                Some((wiring, weight)) => {
+                    let weight_column = column + wiring.len() + 1;
+                    let weight = weight
+                        .parse()
+                        .map_err(|_| ParseMachineError::new(weight_column, "invalid weight"))?;
+                    (wiring, weight)
+                }
+                None => (token, 1),
+            };
+
+            let list = wiring
+                .strip_prefix('(')
+                .and_then(|w| w.strip_suffix(')'))
+                .ok_or(ParseMachineError::new(column, "expected (button wiring)"))?;
+
+            let button = parse_list::<u16>(list, column + 1, "invalid light index")?
+                .into_iter()
+                .map(|(light_column, light)| {
+                    if (light as usize) < lights.len() {
+                        Ok(light)
+                    } else {
+                        Err(ParseMachineError::new(
+                            light_column,
+                            "light index out of range",
+                        ))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if buttons.len() == MAX_BUTTONS {
+                return Err(ParseMachineError::new(column, "too many buttons"));
+            }
+
+            buttons.push(button);
+            weights.push(weight);
+        }
+
+        let joltages = joltages.ok_or(ParseMachineError::new(s.len() + 1, "missing joltages"))?;
+
+        Ok(Self {
+            lights,
+            buttons,
+            weights,
+            joltages,
+        })
+    }
+}
 
 #[derive(Debug)]
 struct GF2System {
     width: u8,
     target: u16,
     buttons: Vec<u16>,
+    weights: Vec<u32>,
 }
 
 impl GF2System {
-    fn new(width: u8, target: u16, buttons: Vec<u16>) -> Self {
+    fn new(width: u8, target: u16, buttons: Vec<u16>, weights: Vec<u32>) -> Self {
         Self {
             width,
             target,
             buttons,
+            weights,
         }
     }
 
-    fn solve(&self) -> u16 {
+    fn solve(&self) -> u64 {
+        let reduced = self.reduce();
+        reduced.solve_with(Enumeration::for_free(reduced.free_weights.len()))
+    }
+
+    fn reduce(&self) -> ReducedGF2System {
         let num_buttons = self.buttons.len();
 
-        let mut augmented: Vec<u16> = (0..self.width)
+        let mut augmented: Vec<u64> = (0..self.width)
             .map(|light| {
-                let button_bits: u16 = self
+                let button_bits: u64 = self
                     .buttons
                     .iter()
                     .enumerate()
                     .filter(|&(_, button)| button & (1 << light) != 0)
-                    .map(|(button_idx, _)| 1u16 << button_idx)
+                    .map(|(button_idx, _)| 1u64 << button_idx)
                     .fold(0, |acc, n| acc | n);
 
-                let target_bit = ((self.target >> light) & 1) << num_buttons;
+                let target_bit = (((self.target >> light) & 1) as u64) << num_buttons;
 
                 button_bits | target_bit
             })
             .collect();
 
         let mut pivot_row = 0;
+        let mut pivot_cols: Vec<usize> = Vec::new();
 
         for col in 0..num_buttons {
             let mask = 1 << col;
@@ -62,100 +240,183 @@ impl GF2System {
                     }
                 }
 
+                pivot_cols.push(col);
                 pivot_row += 1;
             }
         }
 
-        let button_mask: u16 = (1u16 << num_buttons) - 1;
+        let button_mask: u64 = (1u64 << num_buttons) - 1;
 
-        let mut free_mask: u16 = 0;
-        for col in 0..num_buttons {
-            let count = augmented
-                .iter()
-                .filter(|&row| row & (1 << col) != 0)
-                .count();
-
-            if count > 1 {
-                free_mask |= 1 << col;
-            }
-        }
+        let pivot_mask: u64 = pivot_cols.iter().fold(0, |acc, &col| acc | (1 << col));
+        let free_mask: u64 = button_mask & !pivot_mask;
 
-        let mut base_presses: u16 = 0;
-        let mut constrained_rows: Vec<u16> = Vec::new();
+        let mut reduced = ReducedGF2System::default();
+        let mut constrained_rows: Vec<u64> = Vec::new();
 
-        for &row in &augmented {
-            let button_bits = row & button_mask;
+        for (&row, &col) in augmented.iter().zip(pivot_cols.iter()) {
+            let weight = self.weights[col] as u64;
             let target = (row >> num_buttons) & 1;
 
-            if button_bits == 0 {
-                continue;
-            }
-
-            if (button_bits & free_mask) != 0 {
+            if (row & free_mask) != 0 {
+                reduced.target |= (target as u32) << constrained_rows.len();
+                reduced.pivot_weights.push(weight);
                 constrained_rows.push(row);
             } else {
-                base_presses += target;
+                reduced.base_cost += target * weight;
             }
         }
 
-        let mut min_presses = u16::MAX;
-        let mut combo = free_mask;
-        loop {
-            let free_presses = combo.count_ones() as u16;
-            let mut presses = base_presses + free_presses;
+        for col in (0..num_buttons).filter(|&col| free_mask & (1 << col) != 0) {
+            let rows = constrained_rows
+                .iter()
+                .enumerate()
+                .filter(|&(_, row)| row & (1 << col) != 0)
+                .fold(0, |acc, (row_idx, _)| acc | (1u32 << row_idx));
 
-            for &row in &constrained_rows {
-                let target = (row >> num_buttons) & 1;
-                let free_parity = (row & combo).count_ones() as u16 & 1;
-                presses += target ^ free_parity;
-            }
+            reduced.free_rows.push(rows);
+            reduced.free_weights.push(self.weights[col] as u64);
+        }
 
-            min_presses = min_presses.min(presses);
+        reduced
+    }
+}
 
-            if combo == 0 {
-                break;
-            }
-            combo = (combo - 1) & free_mask;
-        }
+const GRAY_CODE_MAX_FREE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Enumeration {
+    GrayCode,
+    ParityDp,
+}
 
-        min_presses
+impl Enumeration {
+    fn for_free(free: usize) -> Self {
+        if free <= GRAY_CODE_MAX_FREE {
+            Enumeration::GrayCode
+        } else {
+            Enumeration::ParityDp
+        }
     }
 }
 
-#[derive(Debug)]
-struct ParseGF2SystemError;
+#[derive(Debug, Default)]
+struct ReducedGF2System {
+    base_cost: u64,
+    target: u32,
+    pivot_weights: Vec<u64>,
+    free_rows: Vec<u32>,
+    free_weights: Vec<u64>,
+}
 
-impl FromStr for GF2System {
-    type Err = ParseGF2SystemError;
+impl ReducedGF2System {
+    fn solve_with(&self, enumeration: Enumeration) -> u64 {
+        let pivot_costs = self.pivot_costs();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let pieces: Vec<_> = s.split_whitespace().collect();
+        let parity_costs = match enumeration {
+            Enumeration::GrayCode => self.parity_costs(),
+            Enumeration::ParityDp => self.parity_dp(),
+        };
+
+        let min_cost = parity_costs
+            .iter()
+            .enumerate()
+            .map(|(parity, &cost)| cost.saturating_add(pivot_costs[self.target as usize ^ parity]))
+            .min();
+
+        self.base_cost + min_cost.unwrap_or(0)
+    }
 
-        let target_str = pieces.first().ok_or(ParseGF2SystemError)?;
+    fn pivot_costs(&self) -> Vec<u64> {
+        let mut costs = vec![0; 1 << self.pivot_weights.len()];
 
-        let mut width: u8 = 0;
-        let mut target: u16 = 0;
-        for (i, c) in target_str.trim_matches(['[', ']']).char_indices() {
-            if c == '#' {
-                target |= 1 << i;
+        for parity in 1..costs.len() {
+            let lowest = parity.trailing_zeros() as usize;
+            costs[parity] = costs[parity & (parity - 1)] + self.pivot_weights[lowest];
+        }
+
+        costs
+    }
+
+    fn parity_costs(&self) -> Vec<u64> {
+        let mut costs = vec![u64::MAX; 1 << self.pivot_weights.len()];
+
+        let mut parity: usize = 0;
+        let mut cost: u64 = 0;
+        let mut pressed: u64 = 0;
+        costs[0] = 0;
+
+        for step in 1..(1u64 << self.free_weights.len()) {
+            let var = step.trailing_zeros() as usize;
+
+            pressed ^= 1 << var;
+            parity ^= self.free_rows[var] as usize;
+
+            if pressed & (1 << var) != 0 {
+                cost += self.free_weights[var];
+            } else {
+                cost -= self.free_weights[var];
             }
 
-            width += 1;
+            costs[parity] = costs[parity].min(cost);
         }
 
-        let system = pieces
+        costs
+    }
+
+    // Cheapest cost of reaching each parity using every free variable at most
+    // once: O(free * 2^rank) instead of walking all 2^free combinations.
+    fn parity_dp(&self) -> Vec<u64> {
+        let mut best = vec![u64::MAX; 1 << self.pivot_weights.len()];
+        best[0] = 0;
+
+        for (&rows, &weight) in self.free_rows.iter().zip(&self.free_weights) {
+            let before = best.clone();
+
+            for (parity, &cost) in before.iter().enumerate() {
+                if cost != u64::MAX {
+                    let next = parity ^ rows as usize;
+                    best[next] = best[next].min(cost + weight);
+                }
+            }
+        }
+
+        best
+    }
+}
+
+impl From<&Machine> for GF2System {
+    fn from(machine: &Machine) -> Self {
+        let target = machine
+            .lights
             .iter()
-            .skip(1)
-            .filter(|&row| row.starts_with('('))
-            .map(|row| {
-                row.trim_matches(['(', ')'])
-                    .split(',')
-                    .map(|n| 1 << n.parse::<u8>().unwrap())
-                    .fold(0_u16, |equation, bit| equation | bit)
+            .enumerate()
+            .filter(|&(_, &on)| on)
+            .fold(0_u16, |target, (i, _)| target | (1 << i));
+
+        let buttons = machine
+            .buttons
+            .iter()
+            .map(|button| {
+                button
+                    .iter()
+                    .fold(0_u16, |equation, &light| equation | (1 << light))
             })
             .collect();
 
-        Ok(Self::new(width, target, system))
+        Self::new(
+            machine.lights.len() as u8,
+            target,
+            buttons,
+            machine.weights.clone(),
+        )
+    }
+}
+
+impl FromStr for GF2System {
+    type Err = ParseMachineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(&s.parse::<Machine>()?))
     }
 }
 
@@ -178,28 +439,25 @@ fn lcm(a: i64, b: i64) -> i64 {
 struct IntSystem {
     target: Vec<u16>,
     buttons: Vec<Vec<u16>>,
+    weights: Vec<u32>,
 }
 
 impl IntSystem {
-    fn new(target: Vec<u16>, buttons: Vec<Vec<u16>>) -> Self {
-        Self { target, buttons }
+    fn new(target: Vec<u16>, buttons: Vec<Vec<u16>>, weights: Vec<u32>) -> Self {
+        Self {
+            target,
+            buttons,
+            weights,
+        }
     }
 
     fn solve(&self) -> u64 {
-        let num_buttons = self.buttons.len();
-
-        let mut augmented: Vec<Vec<i64>> = (0..self.target.len())
-            .map(|n| {
-                let mut button_row = self
-                    .buttons
-                    .iter()
-                    .map(move |row| if row.contains(&(n as u16)) { 1 } else { 0 })
-                    .collect::<Vec<i64>>();
+        self.reduced_model().solve()
+    }
 
-                button_row.push(self.target[n] as i64);
-                button_row
-            })
-            .collect();
+    fn reduce(&self) -> Vec<Vec<i64>> {
+        let num_buttons = self.buttons.len();
+        let mut augmented = self.augmented();
 
         let mut pivot_row: usize = 0;
 
@@ -241,23 +499,96 @@ impl IntSystem {
             }
         }
 
+        augmented
+    }
+
+    fn augmented(&self) -> Vec<Vec<i64>> {
+        (0..self.target.len())
+            .map(|n| {
+                let mut button_row = self
+                    .buttons
+                    .iter()
+                    .map(move |row| if row.contains(&(n as u16)) { 1 } else { 0 })
+                    .collect::<Vec<i64>>();
+
+                button_row.push(self.target[n] as i64);
+                button_row
+            })
+            .collect()
+    }
+
+    fn original_model(&self) -> LinearModel {
+        self.model("light", self.augmented())
+    }
+
+    fn reduced_model(&self) -> LinearModel {
+        self.model("row", self.reduce())
+    }
+
+    fn model(&self, prefix: &str, augmented: Vec<Vec<i64>>) -> LinearModel {
+        let num_buttons = self.buttons.len();
+
+        LinearModel {
+            name: "day10".to_string(),
+            variables: (0..num_buttons).map(|i| format!("x{}", i)).collect(),
+            objective: self.weights.iter().map(|&weight| weight as i64).collect(),
+            constraints: augmented
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut row)| {
+                    let rhs = row.pop().unwrap();
+                    Constraint {
+                        name: format!("{}{}", prefix, i),
+                        coefficients: row,
+                        rhs,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint {
+    name: String,
+    coefficients: Vec<i64>,
+    rhs: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LinearModel {
+    name: String,
+    variables: Vec<String>,
+    objective: Vec<i64>,
+    constraints: Vec<Constraint>,
+}
+
+impl LinearModel {
+    fn solve(&self) -> u64 {
         let mut vars = ProblemVariables::new();
 
-        let x: Vec<Variable> = (0..num_buttons)
-            .map(|i| vars.add(variable().integer().min(0).name(format!("x{}", i))))
+        let x: Vec<Variable> = self
+            .variables
+            .iter()
+            .map(|name| vars.add(variable().integer().min(0).name(name)))
             .collect();
 
-        let objective: Expression = x.iter().copied().sum();
+        let objective: Expression = x
+            .iter()
+            .zip(self.objective.iter())
+            .map(|(&var, &coef)| (coef as f64) * var)
+            .sum();
         let mut problem = vars.minimise(objective).using(default_solver);
 
-        for (i, _) in augmented.iter().enumerate() {
-            let lhs: Expression = augmented[i][..num_buttons]
+        for constraint in &self.constraints {
+            let lhs: Expression = constraint
+                .coefficients
                 .iter()
                 .zip(x.iter())
-                .map(|(&coef, &var)| (coef as i32) * var)
+                .map(|(&coef, &var)| (coef as f64) * var)
                 .sum();
 
-            let rhs = augmented[i][num_buttons] as i32;
+            let rhs = constraint.rhs as f64;
 
             problem = problem.with(constraint!(lhs.clone() <= rhs));
             problem = problem.with(constraint!(lhs >= rhs));
@@ -266,72 +597,366 @@ impl IntSystem {
         let solution = problem.solve().unwrap();
 
         x.iter()
-            .map(|&v| solution.value(v).round() as i64)
+            .zip(self.objective.iter())
+            .map(|(&v, &coef)| solution.value(v).round() as i64 * coef)
             .sum::<i64>() as u64
     }
-}
 
-#[derive(Debug)]
-struct ParseIntSystemError;
-
-impl FromStr for IntSystem {
-    type Err = ParseIntSystemError;
+    fn to_lp(&self) -> String {
+        let mut out = String::new();
+
+        writeln!(out, "\\ {}", self.name).unwrap();
+        writeln!(out, "Minimize").unwrap();
+        writeln!(out, " obj: {}", self.lp_expression(&self.objective)).unwrap();
+        writeln!(out, "Subject To").unwrap();
+        for constraint in &self.constraints {
+            writeln!(
+                out,
+                " {}: {} = {}",
+                constraint.name,
+                self.lp_expression(&constraint.coefficients),
+                constraint.rhs
+            )
+            .unwrap();
+        }
+        writeln!(out, "Bounds").unwrap();
+        for var in &self.variables {
+            writeln!(out, " {} >= 0", var).unwrap();
+        }
+        writeln!(out, "General").unwrap();
+        writeln!(out, " {}", self.variables.join(" ")).unwrap();
+        writeln!(out, "End").unwrap();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let pieces: Vec<&str> = s.split_whitespace().collect();
+        out
+    }
 
-        let buttons: Vec<Vec<u16>> = pieces
+    fn lp_expression(&self, coefficients: &[i64]) -> String {
+        let terms: Vec<String> = coefficients
             .iter()
-            .skip(1)
-            .filter(|&row| row.starts_with('('))
-            .map(|row| {
-                row.trim_matches(['(', ')'])
-                    .split(',')
-                    .map(|n| n.parse().unwrap())
-                    .collect()
+            .zip(self.variables.iter())
+            .filter(|&(&coef, _)| coef != 0)
+            .enumerate()
+            .map(|(i, (&coef, var))| {
+                let sign = match (i, coef < 0) {
+                    (0, false) => "",
+                    (0, true) => "- ",
+                    (_, false) => "+ ",
+                    (_, true) => "- ",
+                };
+                format!("{}{} {}", sign, coef.abs(), var)
             })
             .collect();
 
-        if buttons.is_empty() {
-            return Err(ParseIntSystemError);
+        if terms.is_empty() {
+            format!("0 {}", self.variables[0])
+        } else {
+            terms.join(" ")
         }
+    }
 
-        let target: Vec<u16> = pieces
-            .iter()
-            .last()
-            .ok_or(ParseIntSystemError)?
-            .trim_matches(['{', '}'])
-            .split(',')
-            .map(|n| n.parse().unwrap())
-            .collect();
+    fn from_lp(s: &str) -> Result<Self, ParseLinearModelError> {
+        let mut name = String::new();
+        let mut objective: Terms = Vec::new();
+        let mut constraints: Vec<(String, Terms, i64)> = Vec::new();
+        let mut variables: Vec<String> = Vec::new();
+        let mut section = "";
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(comment) = line.strip_prefix('\\') {
+                name = comment.trim().to_string();
+                continue;
+            }
+
+            match line {
+                "Minimize" | "Subject To" | "Bounds" | "General" => {
+                    section = line;
+                    continue;
+                }
+                "End" => break,
+                _ => {}
+            }
 
-        if target.is_empty() {
-            return Err(ParseIntSystemError);
+            match section {
+                "Minimize" => {
+                    let (_, expr) = line.split_once(':').ok_or(ParseLinearModelError)?;
+                    objective = parse_lp_terms(expr)?;
+                }
+                "Subject To" => {
+                    let (label, rest) = line.split_once(':').ok_or(ParseLinearModelError)?;
+                    let (expr, rhs) = rest.split_once('=').ok_or(ParseLinearModelError)?;
+                    let rhs = rhs.trim().parse().map_err(|_| ParseLinearModelError)?;
+                    constraints.push((label.trim().to_string(), parse_lp_terms(expr)?, rhs));
+                }
+                "Bounds" => {
+                    if line.split_whitespace().collect::<Vec<_>>()[1..] != [">=", "0"] {
+                        return Err(ParseLinearModelError);
+                    }
+                }
+                "General" => variables.extend(line.split_whitespace().map(String::from)),
+                _ => return Err(ParseLinearModelError),
+            }
         }
 
-        Ok(IntSystem::new(target, buttons))
+        let dense = |terms: &Terms| -> Result<Vec<i64>, ParseLinearModelError> {
+            let mut coefficients = vec![0; variables.len()];
+            for (var, coef) in terms {
+                let i = variables
+                    .iter()
+                    .position(|v| v == var)
+                    .ok_or(ParseLinearModelError)?;
+                coefficients[i] += coef;
+            }
+            Ok(coefficients)
+        };
+
+        Ok(Self {
+            name,
+            objective: dense(&objective)?,
+            constraints: constraints
+                .iter()
+                .map(|(name, terms, rhs)| {
+                    Ok(Constraint {
+                        name: name.clone(),
+                        coefficients: dense(terms)?,
+                        rhs: *rhs,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            variables,
+        })
+    }
+
+    fn to_mps(&self) -> String {
+        let mut out = String::new();
+
+        writeln!(out, "NAME {}", self.name).unwrap();
+        writeln!(out, "ROWS").unwrap();
+        writeln!(out, " N obj").unwrap();
+        for constraint in &self.constraints {
+            writeln!(out, " E {}", constraint.name).unwrap();
+        }
+        writeln!(out, "COLUMNS").unwrap();
+        writeln!(out, " MARKER 'MARKER' 'INTORG'").unwrap();
+        for (i, var) in self.variables.iter().enumerate() {
+            writeln!(out, " {} obj {}", var, self.objective[i]).unwrap();
+            for constraint in &self.constraints {
+                if constraint.coefficients[i] != 0 {
+                    writeln!(
+                        out,
+                        " {} {} {}",
+                        var, constraint.name, constraint.coefficients[i]
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        writeln!(out, " MARKER 'MARKER' 'INTEND'").unwrap();
+        writeln!(out, "RHS").unwrap();
+        for constraint in &self.constraints {
+            writeln!(out, " RHS {} {}", constraint.name, constraint.rhs).unwrap();
+        }
+        writeln!(out, "BOUNDS").unwrap();
+        for var in &self.variables {
+            writeln!(out, " LO BND {} 0", var).unwrap();
+        }
+        writeln!(out, "ENDATA").unwrap();
+
+        out
+    }
+
+    fn from_mps(s: &str) -> Result<Self, ParseLinearModelError> {
+        let mut model = Self {
+            name: String::new(),
+            variables: Vec::new(),
+            objective: Vec::new(),
+            constraints: Vec::new(),
+        };
+        let mut section = "";
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if !line.starts_with(' ') {
+                section = fields[0];
+                if section == "NAME" {
+                    model.name = fields[1..].join(" ");
+                }
+                continue;
+            }
+
+            match (section, fields.as_slice()) {
+                ("ROWS", ["N", _]) => {}
+                ("ROWS", ["E", name]) => model.constraints.push(Constraint {
+                    name: name.to_string(),
+                    coefficients: Vec::new(),
+                    rhs: 0,
+                }),
+                ("COLUMNS", [_, "'MARKER'", _]) => {}
+                ("COLUMNS", [var, row, value]) => {
+                    let value: i64 = value.parse().map_err(|_| ParseLinearModelError)?;
+
+                    if model.variables.last().map(String::as_str) != Some(var) {
+                        model.variables.push(var.to_string());
+                        model.objective.push(0);
+                        for constraint in model.constraints.iter_mut() {
+                            constraint.coefficients.push(0);
+                        }
+                    }
+
+                    if *row == "obj" {
+                        *model.objective.last_mut().unwrap() = value;
+                    } else {
+                        let constraint = model
+                            .constraints
+                            .iter_mut()
+                            .find(|c| c.name == *row)
+                            .ok_or(ParseLinearModelError)?;
+                        *constraint.coefficients.last_mut().unwrap() = value;
+                    }
+                }
+                ("RHS", [_, row, value]) => {
+                    model
+                        .constraints
+                        .iter_mut()
+                        .find(|c| c.name == *row)
+                        .ok_or(ParseLinearModelError)?
+                        .rhs = value.parse().map_err(|_| ParseLinearModelError)?;
+                }
+                ("BOUNDS", ["LO", _, _, "0"]) => {}
+                _ => return Err(ParseLinearModelError),
+            }
+        }
+
+        Ok(model)
     }
 }
 
-fn part1(systems: &[GF2System]) -> u16 {
-    systems.iter().map(|s| s.solve()).sum()
+type Terms = Vec<(String, i64)>;
+
+fn parse_lp_terms(expr: &str) -> Result<Terms, ParseLinearModelError> {
+    let mut terms = Vec::new();
+    let mut sign = 1;
+    let mut coef: Option<i64> = None;
+
+    for token in expr.split_whitespace() {
+        match token {
+            "+" => sign = 1,
+            "-" => sign = -1,
+            _ => match token.parse::<i64>() {
+                Ok(n) => coef = Some(n),
+                Err(_) => {
+                    terms.push((token.to_string(), sign * coef.unwrap_or(1)));
+                    sign = 1;
+                    coef = None;
+                }
+            },
+        }
+    }
+
+    if coef.is_some() {
+        return Err(ParseLinearModelError);
+    }
+
+    Ok(terms)
 }
 
-fn part2(systems: &[IntSystem]) -> u64 {
-    let mut sum: u64 = 0;
-    for sys in systems {
-        sum += sys.solve();
+#[derive(Debug)]
+struct ParseLinearModelError;
+
+fn export_models(systems: &[IntSystem], dir: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    for (i, sys) in systems.iter().enumerate() {
+        let line = i + 1;
+
+        for (kind, mut model) in [
+            ("original", sys.original_model()),
+            ("reduced", sys.reduced_model()),
+        ] {
+            model.name = format!("day10_line{:03}_{}", line, kind);
+
+            let path = format!("{}/{}", dir, model.name);
+            std::fs::write(format!("{}.lp", path), model.to_lp())?;
+            std::fs::write(format!("{}.mps", path), model.to_mps())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn load_model(path: &str) -> Result<LinearModel, ParseLinearModelError> {
+    let contents = std::fs::read_to_string(path).map_err(|_| ParseLinearModelError)?;
+
+    if path.ends_with(".mps") {
+        LinearModel::from_mps(&contents)
+    } else {
+        LinearModel::from_lp(&contents)
+    }
+}
+
+impl From<&Machine> for IntSystem {
+    fn from(machine: &Machine) -> Self {
+        Self::new(
+            machine.joltages.clone(),
+            machine.buttons.clone(),
+            machine.weights.clone(),
+        )
     }
-    sum
+}
+
+impl FromStr for IntSystem {
+    type Err = ParseMachineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(&s.parse::<Machine>()?))
+    }
+}
+
+fn part1(systems: &[GF2System]) -> u64 {
+    let costs: Vec<u64> = systems.par_iter().map(|s| s.solve()).collect();
+    costs.iter().sum()
+}
+
+fn part2(systems: &[IntSystem]) -> u64 {
+    let costs: Vec<u64> = systems.par_iter().map(|s| s.solve()).collect();
+    costs.iter().sum()
 }
 
 fn main() {
-    let lines = read_lines(10);
-    let gf2_systems: Vec<GF2System> = lines.iter().map(|line| line.parse().unwrap()).collect();
-    let int_systems: Vec<IntSystem> = lines.iter().map(|line| line.parse().unwrap()).collect();
+    let machines: Vec<Machine> = read_lines(10)
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse()
+                .unwrap_or_else(|err| panic!("line {}, {}", i + 1, err))
+        })
+        .collect();
+    let gf2_systems: Vec<GF2System> = machines.iter().map(GF2System::from).collect();
+    let int_systems: Vec<IntSystem> = machines.iter().map(IntSystem::from).collect();
+
+    if let Some(dir) = arg_value("--export") {
+        export_models(&int_systems, &dir).expect("Failed to export models");
+    }
 
-    timed!("Part 1", part1(&gf2_systems));
-    timed!("Part 2", part2(&int_systems));
+    if let Some(path) = arg_value("--solve-model") {
+        let model = load_model(&path).expect("Failed to load model");
+        timed!(model.name, model.solve());
+        return;
+    }
+
+    let threads: usize = arg_value("--threads")
+        .map(|n| n.parse().expect("--threads requires a number"))
+        .unwrap_or(0);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Failed to build thread pool");
+
+    pool.install(|| {
+        let threads = pool.current_num_threads();
+        timed!(format!("Part 1 ({} threads)", threads), part1(&gf2_systems));
+        timed!(format!("Part 2 ({} threads)", threads), part2(&int_systems));
+    });
 }
 
 #[cfg(test)]
@@ -365,6 +990,154 @@ mod tests {
         assert_eq!(part2(&systems), 158);
     }
 
+    const WEIGHTED: &str = "[.##.] (3):1 (1,3):4 (2) (2,3):2 (0,2):3 (0,1):3 {3,5,4,7}";
+
+    #[test]
+    fn test_part1_thread_counts() {
+        let systems: Vec<GF2System> = FIXTURE
+            .lines()
+            .chain(WEIGHTED.lines())
+            .map(|line| line.parse().unwrap())
+            .collect();
+
+        for threads in [1, 4] {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            assert_eq!(pool.install(|| part1(&systems)), 13);
+        }
+    }
+
+    #[test]
+    fn test_part1_weighted() {
+        let systems: Vec<GF2System> = WEIGHTED.lines().map(|line| line.parse().unwrap()).collect();
+        assert_eq!(part1(&systems), 6);
+    }
+
+    #[test]
+    fn test_part2_weighted() {
+        let systems: Vec<IntSystem> = WEIGHTED.lines().map(|line| line.parse().unwrap()).collect();
+        assert_eq!(part2(&systems), 26);
+    }
+
+    const WIDE: &str = "[#.##.] (0):2 (1):3 (0,1):4 (2):5 (0,2):6 (1,2):7 (0,1,2):1 (3):2 (0,3):3 (1,3):4 (0,1,3):5 (2,3):6 (0,2,3):7 (1,2,3):1 (0,1,2,3):2 (4):3 (0,4):4 (1,4):5 (0,1,4):6 (2,4):7 (0,2,4):1 (1,2,4):2 (0,1,2,4):3 (3,4):4 (0,3,4):5 (1,3,4):6 (0,1,3,4):7 (2,3,4):1 {1,1,1,1,1}";
+
+    #[test]
+    fn test_part1_many_free_variables() {
+        let systems: Vec<GF2System> = WIDE.lines().map(|line| line.parse().unwrap()).collect();
+        assert_eq!(
+            Enumeration::for_free(systems[0].reduce().free_weights.len()),
+            Enumeration::ParityDp
+        );
+        assert_eq!(part1(&systems), 4);
+    }
+
+    #[test]
+    fn test_part1_forty_free_variables() {
+        let pairs = (0..6).flat_map(|a| (a + 1..6).map(move |b| (a, b)));
+        let decoys = (3..6).flat_map(|weight| {
+            pairs
+                .clone()
+                .map(move |(a, b)| format!("({a},{b}):{weight}"))
+        });
+        let buttons: Vec<String> = decoys
+            .chain(["(0,1,2):4".into(), "(3,4,5):4".into()])
+            .collect();
+        let line = format!("[######] {} {{1,1,1,1,1,1}}", buttons.join(" "));
+
+        let reduced = line.parse::<GF2System>().unwrap().reduce();
+        assert!(reduced.free_weights.len() >= 40);
+        assert_eq!(reduced.solve_with(Enumeration::ParityDp), 8);
+    }
+
+    #[test]
+    fn test_enumerations_agree() {
+        for line in FIXTURE.lines().chain(WEIGHTED.lines()).chain(WIDE.lines()) {
+            let reduced = line.parse::<GF2System>().unwrap().reduce();
+            assert_eq!(
+                reduced.solve_with(Enumeration::GrayCode),
+                reduced.solve_with(Enumeration::ParityDp)
+            );
+        }
+    }
+
+    #[test]
+    fn test_original_model_lp() {
+        let system: IntSystem = "[.#] (0) (0,1):2 {3,2}".parse().unwrap();
+        assert_eq!(
+            system.original_model().to_lp(),
+            "\\ day10
+Minimize
+ obj: 1 x0 + 2 x1
+Subject To
+ light0: 1 x0 + 1 x1 = 3
+ light1: 1 x1 = 2
+Bounds
+ x0 >= 0
+ x1 >= 0
+General
+ x0 x1
+End
+"
+        );
+    }
+
+    #[test]
+    fn test_model_round_trip() {
+        for line in FIXTURE.lines().chain(WEIGHTED.lines()) {
+            let system: IntSystem = line.parse().unwrap();
+
+            for model in [system.original_model(), system.reduced_model()] {
+                assert_eq!(LinearModel::from_lp(&model.to_lp()).unwrap(), model);
+                assert_eq!(LinearModel::from_mps(&model.to_mps()).unwrap(), model);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_machine() {
+        let machine: Machine = "[.#] (0) (0,1):2 {3,2}".parse().unwrap();
+        assert_eq!(
+            machine,
+            Machine {
+                lights: vec![false, true],
+                buttons: vec![vec![0], vec![0, 1]],
+                weights: vec![1, 2],
+                joltages: vec![3, 2],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_machine_errors() {
+        let cases = [
+            ("", 1, "missing indicator diagram"),
+            ("[.x] (0) {1,2}", 3, "expected '.' or '#'"),
+            ("[.#] (0,2) {1,2}", 9, "light index out of range"),
+            ("[.#] (0,a) {1,2}", 9, "invalid light index"),
+            ("[.#] (0):x {1,2}", 10, "invalid weight"),
+            ("[.#] 0 {1,2}", 6, "expected (button wiring)"),
+            (
+                "[.#] (0) {1}",
+                10,
+                "joltage count does not match light count",
+            ),
+            ("[.#] (0) {1,2} (1)", 16, "unexpected token after joltages"),
+            ("[.#] (0)", 9, "missing joltages"),
+            ("[.#] {1,2}", 6, "missing button wirings"),
+        ];
+
+        for (line, column, reason) in cases {
+            assert_eq!(
+                line.parse::<Machine>(),
+                Err(ParseMachineError::new(column, reason)),
+                "{}",
+                line
+            );
+        }
+    }
+
     #[test]
     fn test_lcm() {
         assert_eq!(lcm(12, 18), 36);
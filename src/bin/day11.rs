@@ -1,106 +1,855 @@
-use std::collections::VecDeque;
+use std::{cmp::Reverse, collections::HashMap, fmt::Write, str::FromStr};
 
-use aoc2025_rs::{read_lines, timed};
+use aoc2025_rs::{
+    arg_value,
+    dag::{self, CycleError, Dag},
+    has_flag, read_lines, timed,
+};
 
 #[derive(Debug)]
-struct Graph<'a> {
-    keys: Vec<&'a str>,
-    adj: Vec<Vec<usize>>,
-    inbound: Vec<usize>,
+struct Graph {
+    dag: Dag,
+    order: Vec<usize>,
+    rank: Vec<usize>,
+    preds: Vec<Vec<usize>>,
 }
 
-impl<'a> Graph<'a> {
-    fn count_paths(&self, src: &str, dest: &str) -> usize {
-        let mut inbound = self.inbound.to_vec();
-        let mut queue = VecDeque::new();
+impl Graph {
+    fn new(dag: Dag) -> Result<Self, CycleError> {
+        let order = dag.topological_order()?;
 
-        for (i, &degree) in self.inbound.iter().enumerate() {
-            if degree == 0 {
-                queue.push_back(i);
+        let mut rank = vec![0; dag.len()];
+        for (i, &node) in order.iter().enumerate() {
+            rank[node] = i;
+        }
+
+        let preds = dag.predecessors();
+
+        Ok(Self {
+            dag,
+            order,
+            rank,
+            preds,
+        })
+    }
+
+    fn id(&self, name: &str) -> Result<usize, QueryError> {
+        self.dag
+            .id(name)
+            .ok_or_else(|| QueryError::UnknownDevice(name.to_string()))
+    }
+
+    // Nodes on some path into one of `dests`. Counts anywhere else never reach
+    // the result, so the DPs skip them rather than overflow on them.
+    fn reaching(&self, dests: &[usize]) -> Vec<bool> {
+        let mut mask = vec![false; self.dag.len()];
+
+        for &dest in dests {
+            for (m, r) in mask.iter_mut().zip(dag::reachable(&self.preds, dest)) {
+                *m |= r;
             }
         }
 
-        let mut order: Vec<usize> = Vec::new();
+        mask
+    }
+
+    fn paths_from<C: PathCount>(
+        &self,
+        src: usize,
+        blocked: &[bool],
+    ) -> Result<Vec<C>, PathCountOverflow> {
+        let mut paths: Vec<C> = vec![C::ZERO; self.dag.len()];
+        paths[src] = C::ONE;
+
+        for &node in &self.order[self.rank[src]..] {
+            if blocked[node] {
+                paths[node] = C::ZERO;
+                continue;
+            }
+
+            for &neighbor in self.dag.neighbours(node) {
+                paths[neighbor] = paths[neighbor].try_add(paths[node])?;
+            }
+        }
+
+        Ok(paths)
+    }
+
+    fn paths_to<C: PathCount>(
+        &self,
+        dest: usize,
+        blocked: &[bool],
+    ) -> Result<Vec<C>, PathCountOverflow> {
+        let mut paths: Vec<C> = vec![C::ZERO; self.dag.len()];
+        paths[dest] = C::ONE;
+
+        for &node in self.order[..self.rank[dest]].iter().rev() {
+            if blocked[node] {
+                continue;
+            }
+
+            for &neighbor in self.dag.neighbours(node) {
+                paths[node] = paths[node].try_add(paths[neighbor])?;
+            }
+        }
+
+        if blocked[dest] {
+            paths[dest] = C::ZERO;
+        }
+
+        Ok(paths)
+    }
+
+    fn paths_through<C: PathCount>(&self, src: &str, dest: &str) -> Result<Vec<C>, QueryError> {
+        let (src, dest) = (self.id(src)?, self.id(dest)?);
+        let unreaching: Vec<bool> = self.reaching(&[dest]).iter().map(|&r| !r).collect();
+        let unreachable: Vec<bool> = self.dag.reachable_from(src).iter().map(|&r| !r).collect();
+
+        let from_src = self.paths_from::<C>(src, &unreaching)?;
+        let to_dest = self.paths_to::<C>(dest, &unreachable)?;
+
+        Ok(from_src
+            .iter()
+            .zip(to_dest.iter())
+            .map(|(&from, &to)| from.try_mul(to))
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn dominator_tree(&self, src: &str) -> Result<Vec<Option<usize>>, QueryError> {
+        Ok(dag::dominators(
+            self.dag.adjacency(),
+            &self.preds,
+            self.id(src)?,
+        ))
+    }
+
+    fn post_dominator_tree(&self, dest: &str) -> Result<Vec<Option<usize>>, QueryError> {
+        Ok(dag::dominators(
+            &self.preds,
+            self.dag.adjacency(),
+            self.id(dest)?,
+        ))
+    }
+
+    fn mandatory_nodes(&self, src: &str, dest: &str) -> Result<Vec<&str>, QueryError> {
+        let (src, dest) = (self.id(src)?, self.id(dest)?);
+        let idom = dag::dominators(self.dag.adjacency(), &self.preds, src);
+
+        if idom[dest].is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut node = dest;
+        let mut mandatory = vec![node];
+
+        while node != src {
+            node = idom[node].unwrap();
+            mandatory.push(node);
+        }
+
+        Ok(mandatory
+            .into_iter()
+            .rev()
+            .map(|node| self.dag.name(node))
+            .collect())
+    }
+
+    fn paths(&self, src: &str, dest: &str) -> Result<Paths<'_>, QueryError> {
+        let (src, dest) = (self.id(src)?, self.id(dest)?);
+        let useful = dag::reachable(&self.preds, dest);
 
-        while let Some(node) = queue.pop_front() {
-            order.push(node);
+        Ok(Paths {
+            graph: self,
+            dest,
+            stack: if useful[src] { vec![(src, 0)] } else { vec![] },
+            useful,
+        })
+    }
+
+    fn names(&self, path: &[usize]) -> Vec<&str> {
+        path.iter().map(|&node| self.dag.name(node)).collect()
+    }
 
-            for &neighbor in &self.adj[node] {
-                inbound[neighbor] -= 1;
+    fn shortest_path(&self, src: &str, dest: &str) -> Result<Option<Vec<&str>>, QueryError> {
+        let (src, dest) = (self.id(src)?, self.id(dest)?);
+        let mut dist: Vec<Option<(usize, usize)>> = vec![None; self.dag.len()];
+        dist[src] = Some((0, src));
 
-                if inbound[neighbor] == 0 {
-                    queue.push_back(neighbor);
+        for &node in &self.order[self.rank[src]..] {
+            let Some((len, _)) = dist[node] else {
+                continue;
+            };
+
+            for &neighbor in self.dag.neighbours(node) {
+                if dist[neighbor].is_none_or(|(best, _)| len + 1 < best) {
+                    dist[neighbor] = Some((len + 1, node));
                 }
             }
         }
 
-        let src_i = self.keys.iter().position(|&key| key == src).unwrap();
-        let dest_i = self.keys.iter().position(|&key| key == dest).unwrap();
+        if dist[dest].is_none() {
+            return Ok(None);
+        }
+
+        let mut path = vec![dest];
+        while let Some(&node) = path.last()
+            && node != src
+        {
+            path.push(dist[node].unwrap().1);
+        }
+        path.reverse();
+
+        Ok(Some(self.names(&path)))
+    }
+
+    fn longest_path(&self, src: &str, dest: &str) -> Result<Option<Vec<&str>>, QueryError> {
+        Ok(self.k_longest_paths(src, dest, 1)?.pop())
+    }
+
+    fn k_longest_paths(
+        &self,
+        src: &str,
+        dest: &str,
+        k: usize,
+    ) -> Result<Vec<Vec<&str>>, QueryError> {
+        let (src, dest) = (self.id(src)?, self.id(dest)?);
 
-        let mut paths: Vec<usize> = vec![0; self.keys.len()];
-        paths[src_i] = 1;
+        // Each entry is (edge count, predecessor node, index into the predecessor's entries).
+        let mut best: Vec<Vec<(usize, usize, usize)>> = vec![Vec::new(); self.dag.len()];
+        best[src].push((0, src, 0));
 
-        for &node in &order {
-            for &neighbor in &self.adj[node] {
-                paths[neighbor] += paths[node];
+        for &node in &self.order[self.rank[src]..] {
+            best[node].sort_by_key(|&(len, _, _)| Reverse(len));
+            best[node].truncate(k);
+
+            for &neighbor in self.dag.neighbours(node) {
+                for i in 0..best[node].len() {
+                    let len = best[node][i].0;
+                    best[neighbor].push((len + 1, node, i));
+                }
             }
+
+            if node == dest {
+                break;
+            }
+        }
+
+        Ok((0..best[dest].len())
+            .map(|i| {
+                let mut path = vec![dest];
+                let (mut node, mut entry) = (dest, i);
+
+                while node != src {
+                    let (_, prev, prev_entry) = best[node][entry];
+                    path.push(prev);
+                    (node, entry) = (prev, prev_entry);
+                }
+                path.reverse();
+
+                self.names(&path)
+            })
+            .collect())
+    }
+
+    fn forbidden_mask(&self, forbidden: &[&str]) -> Result<Vec<bool>, QueryError> {
+        let mut mask = vec![false; self.dag.len()];
+        for name in forbidden {
+            mask[self.id(name)?] = true;
         }
+        Ok(mask)
+    }
 
-        paths[dest_i]
+    fn count_paths_between<C: PathCount>(
+        &self,
+        srcs: &[&str],
+        dests: &[&str],
+        forbidden: &[&str],
+    ) -> Result<Vec<Vec<C>>, QueryError> {
+        let dests: Vec<usize> = dests
+            .iter()
+            .map(|dest| self.id(dest))
+            .collect::<Result<_, _>>()?;
+        let blocked: Vec<bool> = self
+            .forbidden_mask(forbidden)?
+            .iter()
+            .zip(self.reaching(&dests))
+            .map(|(&forbidden, reaching)| forbidden || !reaching)
+            .collect();
+
+        srcs.iter()
+            .map(|src| {
+                let paths = self.paths_from::<C>(self.id(src)?, &blocked)?;
+                Ok(dests.iter().map(|&dest| paths[dest]).collect())
+            })
+            .collect()
+    }
+
+    fn count_paths<C: PathCount>(&self, src: &str, dest: &str) -> Result<C, QueryError> {
+        self.count_paths_via(src, dest, &[], &[])
+    }
+
+    fn count_paths_via<C: PathCount>(
+        &self,
+        src: &str,
+        dest: &str,
+        required: &[&str],
+        forbidden: &[&str],
+    ) -> Result<C, QueryError> {
+        let mut waypoints = required
+            .iter()
+            .map(|&name| Ok((self.rank[self.id(name)?], name)))
+            .collect::<Result<Vec<_>, QueryError>>()?;
+        waypoints.sort();
+        waypoints.dedup();
+
+        let mut chain = vec![src];
+        chain.extend(waypoints.into_iter().map(|(_, name)| name));
+        chain.push(dest);
+
+        let counts =
+            self.count_paths_between::<C>(&chain[..chain.len() - 1], &chain[1..], forbidden)?;
+
+        Ok((0..counts.len()).try_fold(C::ONE, |acc, i| acc.try_mul(counts[i][i]))?)
     }
 }
 
-fn part2(graph: &Graph) -> usize {
-    (graph.count_paths("svr", "fft")
-        * graph.count_paths("fft", "dac")
-        * graph.count_paths("dac", "out"))
-        + (graph.count_paths("svr", "dac")
-            * graph.count_paths("dac", "fft")
-            * graph.count_paths("fft", "out"))
+struct Paths<'a> {
+    graph: &'a Graph,
+    dest: usize,
+    useful: Vec<bool>,
+    stack: Vec<(usize, usize)>,
 }
 
-impl<'a> FromIterator<&'a str> for Graph<'a> {
-    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
-        let mut keys: Vec<&'a str> = Vec::new();
-        let mut edges: Vec<&'a str> = Vec::new();
-        let mut adj: Vec<Vec<usize>> = Vec::new();
+impl<'a> Iterator for Paths<'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, next)) = self.stack.last_mut() {
+            let node = *node;
+
+            if node == self.dest {
+                let path: Vec<usize> = self.stack.iter().map(|&(node, _)| node).collect();
+                self.stack.pop();
+                return Some(self.graph.names(&path));
+            }
 
-        for line in iter {
-            if let Some((k, es)) = line.split_once(": ") {
-                keys.push(k);
-                edges.push(es);
+            match self.graph.dag.neighbours(node).get(*next) {
+                Some(&neighbor) => {
+                    *next += 1;
+                    if self.useful[neighbor] {
+                        self.stack.push((neighbor, 0));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                }
             }
         }
 
-        keys.push("out");
+        None
+    }
+}
 
-        let mut inbound: Vec<usize> = vec![0; keys.len()];
+#[derive(Debug, PartialEq, Eq)]
+struct PathCountOverflow;
 
-        for node_edges in edges {
-            let edges = node_edges
-                .split(' ')
-                .filter_map(|edge| keys.iter().position(|key| key == &edge))
-                .collect();
+#[derive(Debug, PartialEq, Eq)]
+enum QueryError {
+    UnknownDevice(String),
+    Overflow,
+}
+
+impl From<PathCountOverflow> for QueryError {
+    fn from(_: PathCountOverflow) -> Self {
+        QueryError::Overflow
+    }
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::UnknownDevice(name) => write!(f, "unknown device {}", name),
+            QueryError::Overflow => write!(f, "path count overflowed"),
+        }
+    }
+}
+
+trait PathCount: Copy + std::fmt::Display {
+    const ZERO: Self;
+    const ONE: Self;
 
-            for &edge in &edges {
-                inbound[edge] += 1;
+    fn try_add(self, other: Self) -> Result<Self, PathCountOverflow>;
+    fn try_mul(self, other: Self) -> Result<Self, PathCountOverflow>;
+}
+
+macro_rules! checked_path_count {
+    ($($t:ty),*) => {$(
+        impl PathCount for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn try_add(self, other: Self) -> Result<Self, PathCountOverflow> {
+                self.checked_add(other).ok_or(PathCountOverflow)
             }
 
-            adj.push(edges);
+            fn try_mul(self, other: Self) -> Result<Self, PathCountOverflow> {
+                self.checked_mul(other).ok_or(PathCountOverflow)
+            }
         }
+    )*};
+}
+
+checked_path_count!(u64, u128);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ModPrime<const P: u64>(u64);
+
+impl<const P: u64> std::fmt::Display for ModPrime<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (mod {})", self.0, P)
+    }
+}
+
+impl<const P: u64> PathCount for ModPrime<P> {
+    const ZERO: Self = Self(0);
+    const ONE: Self = Self(1 % P);
 
-        adj.push(vec![]);
+    fn try_add(self, other: Self) -> Result<Self, PathCountOverflow> {
+        Ok(Self(
+            ((self.0 as u128 + other.0 as u128) % P as u128) as u64,
+        ))
+    }
 
-        Graph { keys, adj, inbound }
+    fn try_mul(self, other: Self) -> Result<Self, PathCountOverflow> {
+        Ok(Self(
+            ((self.0 as u128 * other.0 as u128) % P as u128) as u64,
+        ))
+    }
+}
+
+fn part2<C: PathCount>(graph: &Graph) -> Result<C, QueryError> {
+    graph.count_paths_via("svr", "out", &["fft", "dac"], &[])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Undeclared {
+    Reject,
+    CreateSink,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ParseGraphError {
+    line: usize,
+    column: usize,
+    reason: String,
+}
+
+impl ParseGraphError {
+    fn new(line: usize, column: usize, reason: &str) -> Self {
+        Self {
+            line,
+            column,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+fn describe_cycle(cycle: &[String]) -> String {
+    let mut names = cycle.to_vec();
+    names.extend(cycle.first().cloned());
+    format!("cycle {}", names.join(" -> "))
+}
+
+impl std::fmt::Display for ParseGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.reason
+        )
+    }
+}
+
+impl Graph {
+    fn parse<S: AsRef<str>>(lines: &[S], undeclared: Undeclared) -> Result<Self, ParseGraphError> {
+        let mut dag = Dag::new();
+        let mut edges: Vec<(usize, usize, usize, &str)> = Vec::new();
+        let mut declared: HashMap<&str, usize> = HashMap::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            let line = line.as_ref();
+            let line_no = i + 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (name, outputs) = line.split_once(':').ok_or(ParseGraphError::new(
+                line_no,
+                1,
+                "expected ':' after device name",
+            ))?;
+
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(ParseGraphError::new(line_no, 1, "invalid device name"));
+            }
+
+            if dag.id(name).is_some() {
+                return Err(ParseGraphError::new(line_no, 1, "duplicate device"));
+            }
+
+            let node = dag.add_node(name);
+            declared.insert(name, line_no);
+
+            for edge in outputs.split_whitespace() {
+                let column = edge.as_ptr() as usize - line.as_ptr() as usize + 1;
+                edges.push((line_no, column, node, edge));
+            }
+        }
+
+        dag.add_node("out");
+
+        for (line_no, column, node, edge) in edges {
+            let edge = match (dag.id(edge), undeclared) {
+                (Some(id), _) => id,
+                (None, Undeclared::CreateSink) => dag.add_node(edge),
+                (None, Undeclared::Reject) => {
+                    return Err(ParseGraphError::new(line_no, column, "undeclared device"));
+                }
+            };
+
+            dag.add_edge(node, edge);
+        }
+
+        Graph::new(dag).map_err(|err| {
+            let line = err
+                .cycle
+                .iter()
+                .find_map(|name| declared.get(name.as_str()).copied())
+                .unwrap_or(1);
+            ParseGraphError::new(line, 1, &describe_cycle(&err.cycle))
+        })
+    }
+}
+
+impl FromStr for Graph {
+    type Err = ParseGraphError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(&s.lines().collect::<Vec<_>>(), Undeclared::Reject)
+    }
+}
+
+impl Graph {
+    fn to_dot<C: PathCount>(
+        &self,
+        src: &str,
+        dest: &str,
+        waypoints: &[&str],
+    ) -> Result<String, QueryError> {
+        let through = self.paths_through::<C>(src, dest)?;
+
+        let mut out = String::from("digraph devices {\n");
+
+        for (node, name) in self.dag.names().iter().enumerate() {
+            let fill = if name == src {
+                Some("palegreen")
+            } else if name == dest {
+                Some("lightcoral")
+            } else if waypoints.contains(&name.as_str()) {
+                Some("gold")
+            } else {
+                None
+            };
+
+            write!(
+                out,
+                "  \"{}\" [label=\"{}\\n{}\"",
+                escape(name),
+                escape(name),
+                through[node]
+            )
+            .unwrap();
+            if let Some(fill) = fill {
+                write!(out, ", style=filled, fillcolor={}", fill).unwrap();
+            }
+            writeln!(out, "];").unwrap();
+        }
+
+        for node in 0..self.dag.len() {
+            for &neighbor in self.dag.neighbours(node) {
+                writeln!(
+                    out,
+                    "  \"{}\" -> \"{}\";",
+                    escape(self.dag.name(node)),
+                    escape(self.dag.name(neighbor))
+                )
+                .unwrap();
+            }
+        }
+
+        out.push_str("}\n");
+        Ok(out)
+    }
+
+    fn to_json(&self) -> String {
+        let entries: Vec<String> = (0..self.dag.len())
+            .map(|node| {
+                let outputs: Vec<String> = self
+                    .dag
+                    .neighbours(node)
+                    .iter()
+                    .map(|&neighbor| json_string(self.dag.name(neighbor)))
+                    .collect();
+
+                format!(
+                    "  {}: [{}]",
+                    json_string(self.dag.name(node)),
+                    outputs.join(", ")
+                )
+            })
+            .collect();
+
+        format!("{{\n{}\n}}\n", entries.join(",\n"))
+    }
+
+    fn from_json(s: &str) -> Result<Self, ParseGraphError> {
+        let mut reader = JsonReader { input: s, pos: 0 };
+        let mut dag = Dag::new();
+        let mut edges: Vec<(usize, usize, String)> = Vec::new();
+        let mut declared: HashMap<String, usize> = HashMap::new();
+
+        reader.expect('{')?;
+
+        if !reader.eat('}') {
+            loop {
+                let key_pos = reader.skip_whitespace();
+                let name = reader.string()?;
+
+                if dag.id(&name).is_some() {
+                    return Err(reader.error_at(key_pos, "duplicate device"));
+                }
+
+                let node = dag.add_node(&name);
+                declared.insert(name, key_pos);
+
+                reader.expect(':')?;
+                reader.expect('[')?;
+
+                if !reader.eat(']') {
+                    loop {
+                        let pos = reader.skip_whitespace();
+                        edges.push((node, pos, reader.string()?));
+
+                        if !reader.eat(',') {
+                            reader.expect(']')?;
+                            break;
+                        }
+                    }
+                }
+
+                if !reader.eat(',') {
+                    reader.expect('}')?;
+                    break;
+                }
+            }
+        }
+
+        if reader.skip_whitespace() != s.len() {
+            return Err(reader.error_at(reader.pos, "unexpected trailing content"));
+        }
+
+        dag.add_node("out");
+
+        for (node, pos, edge) in edges {
+            let edge = dag
+                .id(&edge)
+                .ok_or_else(|| reader.error_at(pos, "undeclared device"))?;
+            dag.add_edge(node, edge);
+        }
+
+        Graph::new(dag).map_err(|err| {
+            let pos = err
+                .cycle
+                .iter()
+                .find_map(|name| declared.get(name).copied())
+                .unwrap_or(0);
+            reader.error_at(pos, &describe_cycle(&err.cycle))
+        })
+    }
+}
+
+// Backslash and quote escaping, shared by JSON strings and quoted DOT IDs.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn json_string(s: &str) -> String {
+    format!("\"{}\"", escape(s))
+}
+
+struct JsonReader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl JsonReader<'_> {
+    fn error_at(&self, pos: usize, reason: &str) -> ParseGraphError {
+        let before = &self.input[..pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        ParseGraphError::new(line, column, reason)
+    }
+
+    fn skip_whitespace(&mut self) -> usize {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.pos
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseGraphError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error_at(self.pos, "unexpected character"))
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseGraphError> {
+        let start = self.skip_whitespace();
+        self.expect('"')?;
+
+        let mut value = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(value);
+                }
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('"' | '\\' | '/'))) => value.push(escaped),
+                    _ => return Err(self.error_at(self.pos + i, "unsupported escape")),
+                },
+                _ => value.push(c),
+            }
+        }
+
+        Err(self.error_at(start, "unterminated string"))
     }
 }
 
 fn main() {
-    let lines = read_lines(11);
-    let graph = lines.iter().map(|s| s.as_str()).collect::<Graph>();
+    let undeclared = if has_flag("--auto-sinks") {
+        Undeclared::CreateSink
+    } else {
+        Undeclared::Reject
+    };
+
+    let graph = match arg_value("--json-input") {
+        Some(path) => {
+            let json = std::fs::read_to_string(path).expect("Failed to read JSON graph");
+            Graph::from_json(&json)
+        }
+        None => Graph::parse(&read_lines(11), undeclared),
+    }
+    .unwrap_or_else(|err| panic!("{}", err));
+
+    if let Some(query) = arg_value("--mandatory") {
+        let (src, dest) = query
+            .split_once(',')
+            .expect("--mandatory requires SRC,DEST");
+        print_mandatory(&graph, src, dest).unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    if let Some(query) = arg_value("--paths") {
+        let (src, dest) = query.split_once(',').expect("--paths requires SRC,DEST");
+        print_paths(&graph, src, dest).unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    if let Some(query) = arg_value("--dot") {
+        let names: Vec<&str> = query.split(',').collect();
+        let [src, dest, waypoints @ ..] = names.as_slice() else {
+            panic!("--dot requires SRC,DEST[,WAYPOINT...]");
+        };
+        let dot = graph
+            .to_dot::<u128>(src, dest, waypoints)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let path = arg_value("--out").unwrap_or_else(|| "day11.dot".to_string());
+        std::fs::write(path, dot).expect("Failed to write DOT file");
+    }
+
+    if let Some(path) = arg_value("--json") {
+        std::fs::write(path, graph.to_json()).expect("Failed to write JSON file");
+    }
+
+    match arg_value("--counter").as_deref() {
+        None | Some("u64") => run::<u64>(&graph),
+        Some("u128") => run::<u128>(&graph),
+        Some("mod") => run::<ModPrime<1_000_000_007>>(&graph),
+        Some(other) => panic!("Unknown counter {}", other),
+    }
+}
+
+fn print_mandatory(graph: &Graph, src: &str, dest: &str) -> Result<(), QueryError> {
+    let through = graph.paths_through::<u128>(src, dest)?;
+    let idom = graph.dominator_tree(src)?;
+    let ipdom = graph.post_dominator_tree(dest)?;
+    let name = |id: Option<usize>| id.map_or("-", |id| graph.dag.name(id));
 
-    timed!("Part 1", graph.count_paths("you", "out"));
-    timed!("Part 2", part2(&graph));
+    for node in graph.mandatory_nodes(src, dest)? {
+        let id = graph.id(node)?;
+        println!(
+            "{}: {} paths, idom {}, ipdom {}",
+            node,
+            through[id],
+            name(idom[id]),
+            name(ipdom[id])
+        );
+    }
+
+    Ok(())
+}
+
+fn print_paths(graph: &Graph, src: &str, dest: &str) -> Result<(), QueryError> {
+    let show = |path: Option<Vec<&str>>| path.map_or("-".to_string(), |path| path.join(" -> "));
+
+    println!("Shortest: {}", show(graph.shortest_path(src, dest)?));
+    println!("Longest: {}", show(graph.longest_path(src, dest)?));
+
+    for (i, path) in graph.k_longest_paths(src, dest, 5)?.into_iter().enumerate() {
+        println!("Longest #{}: {}", i + 1, path.join(" -> "));
+    }
+
+    for path in graph.paths(src, dest)?.take(10) {
+        println!("{}", path.join(" -> "));
+    }
+
+    Ok(())
+}
+
+fn run<C: PathCount>(graph: &Graph) {
+    timed!(
+        "Part 1",
+        graph
+            .count_paths::<C>("you", "out")
+            .unwrap_or_else(|err| panic!("{}", err))
+    );
+    timed!(
+        "Part 2",
+        part2::<C>(graph).unwrap_or_else(|err| panic!("{}", err))
+    );
 }
 
 #[cfg(test)]
@@ -119,8 +868,199 @@ fff: out
 ggg: out
 hhh: ccc fff iii
 iii: out";
-        let graph = input.lines().collect::<Graph>();
-        assert_eq!(graph.count_paths("you", "out"), 5);
+        let graph = input.parse::<Graph>().unwrap();
+        assert_eq!(graph.count_paths::<u64>("you", "out"), Ok(5));
+    }
+
+    #[test]
+    fn test_count_paths_between() {
+        let input = "a: b c
+b: c d
+c: d
+d: out";
+        let graph = input.parse::<Graph>().unwrap();
+        assert_eq!(
+            graph.count_paths_between::<u64>(&["a", "b", "d"], &["c", "d", "out"], &[]),
+            Ok(vec![vec![2, 3, 3], vec![1, 2, 2], vec![0, 1, 1]])
+        );
+    }
+
+    #[test]
+    fn test_count_paths_via() {
+        let input = "a: b c
+b: c d
+c: d
+d: out";
+        let graph = input.parse::<Graph>().unwrap();
+        assert_eq!(graph.count_paths_via::<u64>("a", "out", &["c"], &[]), Ok(2));
+        assert_eq!(
+            graph.count_paths_via::<u64>("a", "out", &["d", "b"], &[]),
+            Ok(2)
+        );
+        assert_eq!(
+            graph.count_paths_via::<u64>("a", "out", &["b"], &["c"]),
+            Ok(1)
+        );
+        assert_eq!(graph.count_paths_via::<u64>("a", "out", &[], &["d"]), Ok(0));
+        assert_eq!(graph.count_paths_via::<u64>("b", "out", &["a"], &[]), Ok(0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("a b c", 1, 1, "expected ':' after device name"),
+            ("a: b\n: out", 2, 1, "invalid device name"),
+            ("a: out\na: out", 2, 1, "duplicate device"),
+            ("a: b\nb: out c", 2, 8, "undeclared device"),
+            ("a: b\nb: a", 1, 1, "cycle a -> b -> a"),
+            ("x: c\na: b\nb: c\nc: b out", 4, 1, "cycle c -> b -> c"),
+        ];
+
+        for (input, line, column, reason) in cases {
+            assert_eq!(
+                input.parse::<Graph>().unwrap_err(),
+                ParseGraphError::new(line, column, reason)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_auto_sinks() {
+        let lines = ["a: b c", "b: c out", "", "c: d e"];
+        let graph = Graph::parse(&lines, Undeclared::CreateSink).unwrap();
+        assert_eq!(graph.count_paths::<u64>("a", "d"), Ok(2));
+        assert_eq!(graph.count_paths::<u64>("a", "out"), Ok(1));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = "a: b c\nb: out\nc: out".parse::<Graph>().unwrap();
+        assert_eq!(
+            graph.to_dot::<u64>("a", "out", &["b"]),
+            Ok("digraph devices {
+  \"a\" [label=\"a\\n2\", style=filled, fillcolor=palegreen];
+  \"b\" [label=\"b\\n1\", style=filled, fillcolor=gold];
+  \"c\" [label=\"c\\n1\"];
+  \"out\" [label=\"out\\n2\", style=filled, fillcolor=lightcoral];
+  \"a\" -> \"b\";
+  \"a\" -> \"c\";
+  \"b\" -> \"out\";
+  \"c\" -> \"out\";
+}
+"
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let graph = "a: b c\nb: out\nc: out b".parse::<Graph>().unwrap();
+        let json = graph.to_json();
+        assert_eq!(
+            json,
+            "{
+  \"a\": [\"b\", \"c\"],
+  \"b\": [\"out\"],
+  \"c\": [\"out\", \"b\"],
+  \"out\": []
+}
+"
+        );
+
+        let imported = Graph::from_json(&json).unwrap();
+        assert_eq!(imported.to_json(), json);
+        assert_eq!(imported.count_paths::<u64>("a", "out"), Ok(3));
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let cases = [
+            ("{\"a\": [\"b\"]}", 1, 8, "undeclared device"),
+            ("{\n  \"a\": [],\n  \"a\": []\n}", 3, 3, "duplicate device"),
+            ("{\"a\" [\"out\"]}", 1, 6, "unexpected character"),
+            ("{\"a\": [\"out]}", 1, 8, "unterminated string"),
+            ("{} {}", 1, 4, "unexpected trailing content"),
+            (
+                "{\"a\": [\"b\"],\n \"b\": [\"a\"]}",
+                1,
+                2,
+                "cycle a -> b -> a",
+            ),
+        ];
+
+        for (input, line, column, reason) in cases {
+            assert_eq!(
+                Graph::from_json(input).unwrap_err(),
+                ParseGraphError::new(line, column, reason)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mandatory_nodes() {
+        let graph = "a: b c\nb: d\nc: d\nd: e out\ne: out\nf: out"
+            .parse::<Graph>()
+            .unwrap();
+        assert_eq!(graph.mandatory_nodes("a", "out"), Ok(vec!["a", "d", "out"]));
+        assert_eq!(graph.mandatory_nodes("b", "e"), Ok(vec!["b", "d", "e"]));
+        assert_eq!(graph.mandatory_nodes("b", "c"), Ok(vec![]));
+
+        let ipdom = graph.post_dominator_tree("out").unwrap();
+        assert_eq!(ipdom[graph.id("b").unwrap()], graph.id("d").ok());
+        assert_eq!(ipdom[graph.id("d").unwrap()], graph.id("out").ok());
+
+        assert_eq!(
+            graph.paths_through::<u64>("a", "out"),
+            Ok(vec![4, 2, 2, 4, 2, 0, 4])
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let graph = "a: b c\nb: d\nc: d out\nd: e out\ne: out\nf: out"
+            .parse::<Graph>()
+            .unwrap();
+        let paths: Vec<Vec<&str>> = graph.paths("a", "out").unwrap().collect();
+        assert_eq!(
+            paths,
+            vec![
+                vec!["a", "b", "d", "e", "out"],
+                vec!["a", "b", "d", "out"],
+                vec!["a", "c", "d", "e", "out"],
+                vec!["a", "c", "d", "out"],
+                vec!["a", "c", "out"],
+            ]
+        );
+        assert_eq!(
+            paths.len() as u64,
+            graph.count_paths::<u64>("a", "out").unwrap()
+        );
+        assert_eq!(graph.paths("b", "c").unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_longest_and_shortest_paths() {
+        let graph = "a: b c\nb: d\nc: d out\nd: e out\ne: out\nf: out"
+            .parse::<Graph>()
+            .unwrap();
+        assert_eq!(
+            graph.shortest_path("a", "out"),
+            Ok(Some(vec!["a", "c", "out"]))
+        );
+        assert_eq!(
+            graph.longest_path("a", "out"),
+            Ok(Some(vec!["a", "b", "d", "e", "out"]))
+        );
+        assert_eq!(
+            graph.k_longest_paths("a", "out", 3),
+            Ok(vec![
+                vec!["a", "b", "d", "e", "out"],
+                vec!["a", "c", "d", "e", "out"],
+                vec!["a", "b", "d", "out"],
+            ])
+        );
+        assert_eq!(graph.shortest_path("f", "a"), Ok(None));
+        assert_eq!(graph.k_longest_paths("f", "a", 3), Ok(vec![]));
     }
 
     #[test]
@@ -138,7 +1078,109 @@ dac: fff
 fff: ggg hhh
 ggg: out
 hhh: out";
-        let graph = input.lines().collect::<Graph>();
-        assert_eq!(part2(&graph), 2);
+        let graph = input.parse::<Graph>().unwrap();
+        assert_eq!(part2::<u64>(&graph), Ok(2));
+    }
+
+    #[test]
+    fn test_count_paths_overflow() {
+        let mut lines: Vec<String> = (0..70)
+            .flat_map(|i| {
+                [
+                    format!("n{}: a{} b{}", i, i, i),
+                    format!("a{}: n{}", i, i + 1),
+                    format!("b{}: n{}", i, i + 1),
+                ]
+            })
+            .collect();
+        lines.push("n70: out".to_string());
+
+        let graph = Graph::parse(&lines, Undeclared::Reject).unwrap();
+        assert_eq!(
+            graph.count_paths::<u64>("n0", "out"),
+            Err(QueryError::Overflow)
+        );
+        assert_eq!(graph.count_paths::<u128>("n0", "out"), Ok(1 << 70));
+        assert_eq!(
+            graph.count_paths::<ModPrime<1_000_000_007>>("n0", "out"),
+            Ok(ModPrime(270016253))
+        );
+    }
+
+    #[test]
+    fn test_unknown_device() {
+        let graph = "a: b\nb: out".parse::<Graph>().unwrap();
+        fn unknown<T>(name: &str) -> Result<T, QueryError> {
+            Err(QueryError::UnknownDevice(name.to_string()))
+        }
+
+        assert_eq!(graph.count_paths::<u64>("a", "zz"), unknown("zz"));
+        assert_eq!(
+            graph.count_paths_via::<u64>("a", "out", &["x"], &[]),
+            unknown("x")
+        );
+        assert_eq!(
+            graph.count_paths_via::<u64>("a", "out", &[], &["y"]),
+            unknown("y")
+        );
+        assert_eq!(graph.mandatory_nodes("q", "out"), unknown("q"));
+        assert!(graph.paths("a", "w").is_err());
+        assert_eq!(
+            graph
+                .count_paths::<u64>("v", "out")
+                .unwrap_err()
+                .to_string(),
+            "unknown device v"
+        );
+    }
+
+    #[test]
+    fn test_overflow_outside_query() {
+        let diamonds = |prefix: &str, end: &str| -> Vec<String> {
+            let mut lines: Vec<String> = (0..70)
+                .flat_map(|i| {
+                    [
+                        format!("{p}{i}: {p}a{i} {p}b{i}", p = prefix),
+                        format!("{p}a{i}: {p}{}", i + 1, p = prefix),
+                        format!("{p}b{i}: {p}{}", i + 1, p = prefix),
+                    ]
+                })
+                .collect();
+            lines.push(format!("{}70: {}", prefix, end));
+            lines
+        };
+
+        let mut lines = diamonds("n", "out");
+        lines.extend(diamonds("m", "d"));
+        lines.extend([
+            "s: n0 t".to_string(),
+            "t: d".to_string(),
+            "d: out".to_string(),
+        ]);
+
+        let graph = Graph::parse(&lines, Undeclared::Reject).unwrap();
+        assert_eq!(graph.count_paths::<u64>("s", "d"), Ok(1));
+        assert_eq!(graph.count_paths_via::<u64>("s", "d", &["t"], &[]), Ok(1));
+        assert_eq!(
+            graph.count_paths_between::<u64>(&["s", "t"], &["t", "d"], &[]),
+            Ok(vec![vec![1, 1], vec![1, 1]])
+        );
+
+        let through = graph.paths_through::<u64>("s", "d").unwrap();
+        assert_eq!(through[graph.id("t").unwrap()], 1);
+        assert_eq!(through[graph.id("n0").unwrap()], 0);
+
+        assert_eq!(
+            graph.count_paths::<u64>("s", "out"),
+            Err(QueryError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_to_dot_escaping() {
+        let graph = "a\"b: c\\d\nc\\d: out".parse::<Graph>().unwrap();
+        let dot = graph.to_dot::<u64>("a\"b", "out", &[]).unwrap();
+        assert!(dot.contains("  \"a\\\"b\" [label=\"a\\\"b\\n1\""));
+        assert!(dot.contains("  \"a\\\"b\" -> \"c\\\\d\";"));
     }
 }
@@ -0,0 +1,372 @@
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Default, Clone)]
+pub struct Dag {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    adj: Vec<Vec<usize>>,
+    inbound: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CycleError {
+    pub cycle: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+impl Dag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn add_node(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.adj.push(Vec::new());
+        self.inbound.push(0);
+        id
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.adj[from].push(to);
+        self.inbound[to] += 1;
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn neighbours(&self, id: usize) -> &[usize] {
+        &self.adj[id]
+    }
+
+    pub fn adjacency(&self) -> &[Vec<usize>] {
+        &self.adj
+    }
+
+    // The reverse adjacency alone, for callers that query the reversed graph
+    // repeatedly and don't need `reversed()` to copy the names as well.
+    pub fn predecessors(&self) -> Vec<Vec<usize>> {
+        let mut preds = vec![Vec::new(); self.len()];
+
+        for (from, edges) in self.adj.iter().enumerate() {
+            for &to in edges {
+                preds[to].push(from);
+            }
+        }
+
+        preds
+    }
+
+    pub fn in_degree(&self, id: usize) -> usize {
+        self.inbound[id]
+    }
+
+    pub fn topological_order(&self) -> Result<Vec<usize>, CycleError> {
+        let mut inbound = self.inbound.to_vec();
+        let mut queue = VecDeque::new();
+
+        for (i, &degree) in self.inbound.iter().enumerate() {
+            if degree == 0 {
+                queue.push_back(i);
+            }
+        }
+
+        let mut order: Vec<usize> = Vec::with_capacity(self.len());
+
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+
+            for &neighbor in &self.adj[node] {
+                inbound[neighbor] -= 1;
+
+                if inbound[neighbor] == 0 {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        if order.len() < self.len() {
+            let cycle = self.find_cycle().unwrap_or_default();
+            return Err(CycleError {
+                cycle: cycle.iter().map(|&id| self.names[id].clone()).collect(),
+            });
+        }
+
+        Ok(order)
+    }
+
+    pub fn find_cycle(&self) -> Option<Vec<usize>> {
+        let mut visit = vec![Visit::New; self.len()];
+
+        for root in 0..self.len() {
+            if visit[root] != Visit::New {
+                continue;
+            }
+
+            let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+            visit[root] = Visit::Active;
+
+            while let Some((node, next)) = stack.last_mut() {
+                let node = *node;
+                let Some(&neighbor) = self.adj[node].get(*next) else {
+                    visit[node] = Visit::Done;
+                    stack.pop();
+                    continue;
+                };
+                *next += 1;
+
+                match visit[neighbor] {
+                    Visit::New => {
+                        visit[neighbor] = Visit::Active;
+                        stack.push((neighbor, 0));
+                    }
+                    Visit::Active => {
+                        let start = stack.iter().position(|&(n, _)| n == neighbor).unwrap();
+                        return Some(stack[start..].iter().map(|&(n, _)| n).collect());
+                    }
+                    Visit::Done => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn reversed(&self) -> Self {
+        let mut reversed = Self {
+            names: self.names.clone(),
+            ids: self.ids.clone(),
+            adj: vec![Vec::new(); self.len()],
+            inbound: vec![0; self.len()],
+        };
+
+        for (from, edges) in self.adj.iter().enumerate() {
+            for &to in edges {
+                reversed.add_edge(to, from);
+            }
+        }
+
+        reversed
+    }
+
+    pub fn reachable_from(&self, src: usize) -> Vec<bool> {
+        reachable(&self.adj, src)
+    }
+
+    pub fn can_reach(&self, src: usize, dest: usize) -> bool {
+        self.reachable_from(src)[dest]
+    }
+
+    pub fn dominators(&self, root: usize) -> Vec<Option<usize>> {
+        dominators(&self.adj, &self.predecessors(), root)
+    }
+}
+
+pub fn reachable(adj: &[Vec<usize>], src: usize) -> Vec<bool> {
+    let mut seen = vec![false; adj.len()];
+    let mut queue = VecDeque::from([src]);
+    seen[src] = true;
+
+    while let Some(node) = queue.pop_front() {
+        for &neighbor in &adj[node] {
+            if !seen[neighbor] {
+                seen[neighbor] = true;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    seen
+}
+
+fn postorder(adj: &[Vec<usize>], root: usize) -> Vec<usize> {
+    let mut seen = vec![false; adj.len()];
+    let mut order = Vec::new();
+    let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+    seen[root] = true;
+
+    while let Some((node, next)) = stack.last_mut() {
+        let node = *node;
+        let Some(&neighbor) = adj[node].get(*next) else {
+            order.push(node);
+            stack.pop();
+            continue;
+        };
+        *next += 1;
+
+        if !seen[neighbor] {
+            seen[neighbor] = true;
+            stack.push((neighbor, 0));
+        }
+    }
+
+    order
+}
+
+// Immediate dominators of every node reachable from `root` along `succs`;
+// `preds` must be the reverse of `succs`.
+pub fn dominators(succs: &[Vec<usize>], preds: &[Vec<usize>], root: usize) -> Vec<Option<usize>> {
+    let postorder = postorder(succs, root);
+
+    let mut index = vec![usize::MAX; succs.len()];
+    for (i, &node) in postorder.iter().enumerate() {
+        index[node] = i;
+    }
+
+    let mut idom: Vec<Option<usize>> = vec![None; succs.len()];
+    idom[root] = Some(root);
+
+    let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+        while a != b {
+            while index[a] < index[b] {
+                a = idom[a].unwrap();
+            }
+            while index[b] < index[a] {
+                b = idom[b].unwrap();
+            }
+        }
+        a
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for &node in postorder.iter().rev().filter(|&&node| node != root) {
+            let new_idom = preds[node]
+                .iter()
+                .filter(|&&pred| idom[pred].is_some())
+                .fold(None, |acc, &pred| match acc {
+                    None => Some(pred),
+                    Some(current) => Some(intersect(&idom, pred, current)),
+                });
+
+            if idom[node] != new_idom {
+                idom[node] = new_idom;
+                changed = true;
+            }
+        }
+    }
+
+    idom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dag(edges: &[(&str, &str)]) -> Dag {
+        let mut dag = Dag::new();
+        for (from, to) in edges {
+            let from = dag.add_node(from);
+            let to = dag.add_node(to);
+            dag.add_edge(from, to);
+        }
+        dag
+    }
+
+    #[test]
+    fn test_interning() {
+        let dag = dag(&[("a", "b"), ("b", "c"), ("a", "c")]);
+        assert_eq!(dag.len(), 3);
+        assert_eq!(dag.id("c"), Some(2));
+        assert_eq!(dag.name(1), "b");
+        assert_eq!(dag.in_degree(2), 2);
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let dag = dag(&[("c", "d"), ("a", "b"), ("b", "c"), ("a", "c")]);
+        let order: Vec<&str> = dag
+            .topological_order()
+            .unwrap()
+            .into_iter()
+            .map(|id| dag.name(id))
+            .collect();
+        assert_eq!(order, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let dag = dag(&[("a", "b"), ("b", "c"), ("c", "d"), ("d", "b")]);
+        assert_eq!(
+            dag.topological_order(),
+            Err(CycleError {
+                cycle: vec!["b".to_string(), "c".to_string(), "d".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn test_dominators() {
+        let dag = dag(&[
+            ("a", "b"),
+            ("a", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("d", "f"),
+            ("g", "f"),
+        ]);
+        let idom: Vec<Option<&str>> = dag
+            .dominators(0)
+            .into_iter()
+            .map(|idom| idom.map(|id| dag.name(id)))
+            .collect();
+        assert_eq!(
+            idom,
+            vec![
+                Some("a"),
+                Some("a"),
+                Some("a"),
+                Some("a"),
+                Some("d"),
+                Some("d"),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reachability() {
+        let dag = dag(&[("a", "b"), ("b", "c"), ("d", "c")]);
+        assert!(dag.can_reach(0, 2));
+        assert!(!dag.can_reach(0, 3));
+
+        let reversed = dag.reversed();
+        assert!(reversed.can_reach(2, 3));
+        assert!(!reversed.can_reach(0, 2));
+
+        let preds = dag.predecessors();
+        assert_eq!(reachable(&preds, 2), reversed.reachable_from(2));
+    }
+}
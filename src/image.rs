@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+pub type Rgb = [u8; 3];
+
+pub fn ppm(width: usize, height: usize, pixels: &[Rgb]) -> Vec<u8> {
+    let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+    out.extend(pixels.iter().flatten());
+    out
+}
+
+pub fn pgm(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    let mut out = format!("P5\n{width} {height}\n255\n").into_bytes();
+    out.extend(pixels);
+    out
+}
+
+pub fn luma([r, g, b]: Rgb) -> u8 {
+    ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+}
+
+const MIN_CODE_SIZE: u8 = 8;
+const MAX_CODES: u16 = 4096;
+
+pub struct Gif {
+    width: u16,
+    height: u16,
+    data: Vec<u8>,
+}
+
+impl Gif {
+    pub fn new(width: u16, height: u16, palette: &[Rgb]) -> Self {
+        let mut data = b"GIF89a".to_vec();
+        data.extend(width.to_le_bytes());
+        data.extend(height.to_le_bytes());
+        data.extend([0xF7, 0, 0]);
+
+        for i in 0..256 {
+            data.extend(palette.get(i).unwrap_or(&[0, 0, 0]));
+        }
+
+        data.extend([0x21, 0xFF, 0x0B]);
+        data.extend(b"NETSCAPE2.0");
+        data.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    pub fn add_frame(&mut self, indices: &[u8], delay_cs: u16) {
+        assert_eq!(indices.len(), self.width as usize * self.height as usize);
+
+        self.data.extend([0x21, 0xF9, 0x04, 0x00]);
+        self.data.extend(delay_cs.to_le_bytes());
+        self.data.extend([0x00, 0x00]);
+
+        self.data.extend([0x2C, 0, 0, 0, 0]);
+        self.data.extend(self.width.to_le_bytes());
+        self.data.extend(self.height.to_le_bytes());
+        self.data.push(0x00);
+
+        self.data.push(MIN_CODE_SIZE);
+        for block in lzw_encode(indices, MIN_CODE_SIZE).chunks(255) {
+            self.data.push(block.len() as u8);
+            self.data.extend(block);
+        }
+        self.data.push(0x00);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.data.push(0x3B);
+        self.data
+    }
+}
+
+struct LzwWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    bits: u8,
+    min_code_size: u8,
+    code_size: u8,
+    decoder_size: u16,
+    first: bool,
+}
+
+impl LzwWriter {
+    fn new(min_code_size: u8) -> Self {
+        let mut writer = Self {
+            bytes: Vec::new(),
+            acc: 0,
+            bits: 0,
+            min_code_size,
+            code_size: min_code_size + 1,
+            decoder_size: 0,
+            first: true,
+        };
+        writer.clear();
+        writer
+    }
+
+    fn write(&mut self, code: u16) {
+        self.acc |= (code as u32) << self.bits;
+        self.bits += self.code_size;
+
+        while self.bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.write(1 << self.min_code_size);
+
+        self.code_size = self.min_code_size + 1;
+        self.decoder_size = (1 << self.min_code_size) + 2;
+        self.first = true;
+    }
+
+    // The decoder learns each code one step behind the encoder, so mirror its
+    // table size to know when it widens the code size.
+    fn emit(&mut self, code: u16) {
+        self.write(code);
+
+        if !self.first && self.decoder_size < MAX_CODES {
+            self.decoder_size += 1;
+        }
+        self.first = false;
+
+        if self.decoder_size == 1 << self.code_size && self.code_size < 12 {
+            self.code_size += 1;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.write((1 << self.min_code_size) + 1);
+
+        if self.bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+pub fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let first_free: u16 = (1 << min_code_size) + 2;
+
+    let mut writer = LzwWriter::new(min_code_size);
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = first_free;
+
+    let Some((&head, rest)) = indices.split_first() else {
+        return writer.finish();
+    };
+
+    let mut prefix = head as u16;
+
+    for &k in rest {
+        if let Some(&code) = dict.get(&(prefix, k)) {
+            prefix = code;
+            continue;
+        }
+
+        writer.emit(prefix);
+
+        if next < MAX_CODES {
+            dict.insert((prefix, k), next);
+            next += 1;
+        } else {
+            writer.clear();
+            dict.clear();
+            next = first_free;
+        }
+
+        prefix = k as u16;
+    }
+
+    writer.emit(prefix);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lzw_decode(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+        let reset = || -> Vec<Vec<u8>> {
+            let mut table: Vec<Vec<u8>> = (0..clear).map(|i| vec![i as u8]).collect();
+            table.push(Vec::new());
+            table.push(Vec::new());
+            table
+        };
+
+        let mut table = reset();
+        let mut code_size = min_code_size + 1;
+        let mut prev: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        let mut pos = 0;
+
+        loop {
+            let mut code = 0;
+            for bit in 0..code_size as usize {
+                let byte = bytes[(pos + bit) / 8];
+                code |= (((byte >> ((pos + bit) % 8)) & 1) as usize) << bit;
+            }
+            pos += code_size as usize;
+
+            if code == clear {
+                table = reset();
+                code_size = min_code_size + 1;
+                prev = None;
+                continue;
+            }
+
+            if code == end {
+                break;
+            }
+
+            let entry = match &prev {
+                _ if code < table.len() => table[code].clone(),
+                Some(p) if code == table.len() => {
+                    let mut entry = p.clone();
+                    entry.push(p[0]);
+                    entry
+                }
+                _ => panic!("invalid code {code}"),
+            };
+
+            out.extend(&entry);
+
+            if let Some(p) = prev
+                && table.len() < MAX_CODES as usize
+            {
+                let mut new_entry = p;
+                new_entry.push(entry[0]);
+                table.push(new_entry);
+            }
+
+            if table.len() == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+
+            prev = Some(entry);
+        }
+
+        out
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        let mut state: u32 = 1;
+        let mut noise = |modulus: u32| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            ((state >> 16) % modulus) as u8
+        };
+
+        let cases: Vec<Vec<u8>> = vec![
+            vec![],
+            vec![7],
+            vec![0; 10_000],
+            (0..50_000).map(|_| noise(3)).collect(),
+            (0..50_000).map(|_| noise(256)).collect(),
+        ];
+
+        for indices in cases {
+            assert_eq!(lzw_decode(&lzw_encode(&indices, 8), 8), indices);
+        }
+    }
+
+    #[test]
+    fn test_netpbm_headers() {
+        assert_eq!(ppm(1, 1, &[[1, 2, 3]]), b"P6\n1 1\n255\n\x01\x02\x03");
+        assert_eq!(pgm(2, 1, &[0, 255]), b"P5\n2 1\n255\n\x00\xff");
+    }
+
+    #[test]
+    fn test_gif_layout() {
+        let mut gif = Gif::new(2, 1, &[[0, 0, 0], [255, 255, 255]]);
+        gif.add_frame(&[0, 1], 10);
+        let bytes = gif.finish();
+
+        assert_eq!(&bytes[..6], b"GIF89a");
+        assert_eq!(&bytes[6..10], &[2, 0, 1, 0]);
+        assert_eq!(&bytes[13..19], &[0, 0, 0, 255, 255, 255]);
+        assert_eq!(bytes.last(), Some(&0x3B));
+    }
+}
@@ -1,3 +1,7 @@
+pub mod bignum;
+pub mod dag;
+pub mod image;
+
 use std::str::FromStr;
 
 pub fn read_lines(day: u8) -> Vec<String> {
@@ -6,6 +10,19 @@ pub fn read_lines(day: u8) -> Vec<String> {
     input.lines().map(String::from).collect()
 }
 
+pub fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag);
+    args.next()?;
+    Some(
+        args.next()
+            .unwrap_or_else(|| panic!("{flag} requires a value")),
+    )
+}
+
+pub fn has_flag(flag: &str) -> bool {
+    std::env::args().any(|arg| arg == flag)
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Range(pub u64, pub u64);
 